@@ -0,0 +1,118 @@
+use std::fs;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::engine::{Engine, Evaluate, Heuristic, Limit};
+use crate::game::{Game, Player};
+use crate::pattern::PatternEval;
+
+// Anything that can pick moves in a game: a search engine, a simple rule,
+// or a remote player
+pub trait Agent {
+    // Short label for reports, e.g. "engine:4"
+    fn name(&self) -> String;
+
+    // Pick a move for the side to move, or None to pass. Only called when
+    // the game is not over.
+    fn choose_move(&mut self, game: &Game) -> Option<(usize, usize)>;
+}
+
+// Plays a uniformly random legal move
+#[derive(Debug, Clone)]
+pub struct RandomAgent {
+    rng: StdRng,
+}
+
+impl RandomAgent {
+    pub fn new(seed: u64) -> RandomAgent {
+        RandomAgent { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Agent for RandomAgent {
+    fn name(&self) -> String {
+        "random".to_string()
+    }
+
+    fn choose_move(&mut self, game: &Game) -> Option<(usize, usize)> {
+        game.legal_moves().choose(&mut self.rng).copied()
+    }
+}
+
+// Plays the move that leaves it with the most discs, as counted by
+// `count_pieces`. Ties go to the first such move in row-major order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GreedyAgent;
+
+impl Agent for GreedyAgent {
+    fn name(&self) -> String {
+        "greedy".to_string()
+    }
+
+    fn choose_move(&mut self, game: &Game) -> Option<(usize, usize)> {
+        let player = game.to_move();
+        let mut best: Option<((usize, usize), usize)> = None;
+        for (row, col) in game.legal_moves() {
+            let mut board = game.board();
+            crate::apply_move(&mut board, player.to_char(), row, col);
+            let (black, white) = crate::count_pieces(&board);
+            let discs = if player == Player::Black { black } else { white };
+            if best.is_none_or(|(_, most)| discs > most) {
+                best = Some(((row, col), discs));
+            }
+        }
+        best.map(|(mv, _)| mv)
+    }
+}
+
+impl<E: Evaluate> Agent for Engine<E> {
+    fn name(&self) -> String {
+        let kind = self.evaluator().name();
+        match self.limit() {
+            Limit::Depth(depth) => format!("{}:{}", kind, depth),
+            Limit::Time(budget) => format!("{}:{}ms", kind, budget.as_millis()),
+            Limit::Clock { .. } => format!("{}:clock", kind),
+        }
+    }
+
+    fn choose_move(&mut self, game: &Game) -> Option<(usize, usize)> {
+        Engine::choose_move(self, game)
+    }
+}
+
+// Build an agent from a short description:
+//   random          uniformly random moves
+//   greedy          most discs after the move
+//   engine:<depth>  alpha-beta search to a fixed depth with the default
+//                   evaluation
+//   pattern:<depth>:<file>
+//                   the same search with pattern weights read from a file
+//                   written by the `tune` subcommand
+// `seed` makes random agents reproducible.
+pub fn parse_agent(spec: &str, seed: u64) -> Result<Box<dyn Agent>, String> {
+    let (kind, arg) = spec.split_once(':').unwrap_or((spec, ""));
+    let parse_depth = |text: &str, example: &str| {
+        text.parse::<u32>()
+            .ok()
+            .filter(|&depth| depth > 0)
+            .ok_or_else(|| format!("'{}' needs a search depth, like {}", spec, example))
+    };
+    match (kind, arg) {
+        ("random", "") => Ok(Box::new(RandomAgent::new(seed))),
+        ("greedy", "") => Ok(Box::new(GreedyAgent)),
+        ("engine", arg) => Ok(Box::new(Engine::new(Heuristic::default(), Limit::Depth(parse_depth(arg, "engine:4")?)))),
+        ("pattern", arg) => {
+            let (arg, path) = arg.split_once(':').unwrap_or((arg, ""));
+            let depth = parse_depth(arg, "pattern:4:weights.txt")?;
+            if path.is_empty() {
+                return Err(format!("'{}' needs a weights file, like pattern:4:weights.txt", spec));
+            }
+            let text = fs::read_to_string(path).map_err(|err| format!("failed to read {}: {}", path, err))?;
+            let eval = PatternEval::parse(&text).map_err(|err| format!("invalid weights in {}: {}", path, err))?;
+            Ok(Box::new(Engine::new(eval, Limit::Depth(depth))))
+        }
+        _ => Err(format!("unknown player '{}': use random, greedy, engine:<depth> or pattern:<depth>:<file>", spec)),
+    }
+}
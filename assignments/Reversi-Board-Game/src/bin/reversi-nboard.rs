@@ -0,0 +1,25 @@
+use reversi::nboard::run;
+
+use clap::{value_parser, Arg, Command};
+use std::io;
+use std::process;
+
+fn main() {
+    let matches = Command::new("reversi-nboard")
+        .about("Run the Reversi engine over the NBoard protocol on stdin and stdout")
+        .arg(
+            Arg::new("depth")
+                .long("depth")
+                .short('d')
+                .value_parser(value_parser!(u32).range(1..))
+                .default_value("6")
+                .help("Search depth until the GUI sets one"),
+        )
+        .get_matches();
+
+    let depth = *matches.get_one::<u32>("depth").unwrap();
+    if let Err(err) = run(io::stdin().lock(), io::stdout(), io::stderr(), depth) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,37 @@
+use reversi::service::{serve, Service, MAX_ENGINE_DEPTH};
+
+use clap::{value_parser, Arg, Command};
+use std::net::TcpListener;
+use std::process;
+use std::sync::Arc;
+
+fn main() {
+    let matches = Command::new("reversi-server")
+        .about("Serve Reversi games over HTTP with a JSON API")
+        .arg(
+            Arg::new("addr")
+                .long("addr")
+                .short('a')
+                .default_value("127.0.0.1:8080")
+                .help("Address to listen on"),
+        )
+        .arg(
+            Arg::new("depth")
+                .long("depth")
+                .short('d')
+                .value_parser(value_parser!(u32).range(1..=MAX_ENGINE_DEPTH as i64))
+                .default_value("6")
+                .help("Engine search depth when a request does not give one"),
+        )
+        .get_matches();
+
+    let addr = matches.get_one::<String>("addr").unwrap();
+    let listener = TcpListener::bind(addr).unwrap_or_else(|err| {
+        eprintln!("Cannot listen on {}: {}", addr, err);
+        process::exit(1);
+    });
+    eprintln!("Serving games on http://{}", listener.local_addr().map_or(addr.clone(), |addr| addr.to_string()));
+
+    let service = Arc::new(Service::new(*matches.get_one::<u32>("depth").unwrap()));
+    serve(&listener, service);
+}
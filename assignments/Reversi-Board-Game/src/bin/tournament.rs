@@ -0,0 +1,112 @@
+use reversi::agent::{parse_agent, Agent};
+use reversi::tournament::{elo_difference, round_robin, Settings};
+
+use clap::{value_parser, Arg, ArgAction, Command};
+use std::process;
+
+fn main() {
+    let matches = Command::new("tournament")
+        .about("Play Reversi agents against each other and rate them")
+        .arg(
+            Arg::new("player")
+                .long("player")
+                .short('p')
+                .action(ArgAction::Append)
+                .required(true)
+                .help("Agent to enter: random, greedy, engine:<depth> or pattern:<depth>:<weights file> (repeat for each player)"),
+        )
+        .arg(
+            Arg::new("games")
+                .long("games")
+                .short('n')
+                .value_parser(value_parser!(usize))
+                .default_value("20")
+                .help("Games per pairing, half with each colour"),
+        )
+        .arg(
+            Arg::new("opening")
+                .long("opening")
+                .value_parser(value_parser!(usize))
+                .default_value("6")
+                .help("Random moves played before the agents take over"),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_parser(value_parser!(u64))
+                .default_value("1")
+                .help("Seed for openings and random agents"),
+        )
+        .get_matches();
+
+    let seed = *matches.get_one::<u64>("seed").unwrap();
+    let mut agents: Vec<Box<dyn Agent>> = matches
+        .get_many::<String>("player")
+        .unwrap()
+        .enumerate()
+        .map(|(i, spec)| {
+            parse_agent(spec, seed.wrapping_add(i as u64 + 1)).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                process::exit(1);
+            })
+        })
+        .collect();
+    if agents.len() < 2 {
+        eprintln!("A tournament needs at least two players.");
+        process::exit(1);
+    }
+
+    let settings = Settings {
+        games: *matches.get_one::<usize>("games").unwrap(),
+        opening_plies: *matches.get_one::<usize>("opening").unwrap(),
+        seed,
+    };
+    let mut played = 0;
+    let standings = round_robin(&mut agents, settings, |_, _, _| {
+        played += 1;
+        eprint!("\rPlayed {} games", played);
+    });
+    eprintln!();
+
+    let names = &standings.names;
+    let width = names.iter().map(String::len).max().unwrap_or(0).max(6);
+
+    println!("{:<w$}  {:<w$}  {:>4} {:>4} {:>4}  {:>6}  {:>6}", "Player", "Opponent", "W", "D", "L", "Score", "Elo", w = width);
+    for i in 0..names.len() {
+        for j in (i + 1)..names.len() {
+            let record = standings.records[i][j];
+            println!(
+                "{:<w$}  {:<w$}  {:>4} {:>4} {:>4}  {:>5.1}%  {:>+6.0}",
+                names[i],
+                names[j],
+                record.wins,
+                record.draws,
+                record.losses,
+                100.0 * record.score(),
+                elo_difference(record.score()),
+                w = width
+            );
+        }
+    }
+
+    println!();
+    let ratings = standings.elo_ratings();
+    let mut order: Vec<usize> = (0..names.len()).collect();
+    order.sort_by(|&a, &b| ratings[b].total_cmp(&ratings[a]));
+    println!("{:>4}  {:<w$}  {:>5} {:>4} {:>4} {:>4}  {:>6}  {:>6}", "Rank", "Player", "Games", "W", "D", "L", "Score", "Elo", w = width);
+    for (rank, &i) in order.iter().enumerate() {
+        let total = standings.total(i);
+        println!(
+            "{:>4}  {:<w$}  {:>5} {:>4} {:>4} {:>4}  {:>5.1}%  {:>+6.0}",
+            rank + 1,
+            names[i],
+            total.games(),
+            total.wins,
+            total.draws,
+            total.losses,
+            100.0 * total.score(),
+            ratings[i],
+            w = width
+        );
+    }
+}
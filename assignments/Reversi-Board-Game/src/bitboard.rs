@@ -0,0 +1,261 @@
+use crate::game::{Cell, Grid, Player};
+use crate::{Board, SIZE};
+
+// Bit `row * 8 + col` is set when the square holds a disc, or is blocked
+// in the `blocked` mask. Only the standard 8x8 board fits in a u64.
+const _: () = assert!(SIZE == 8);
+
+const NOT_FILE_A: u64 = 0xfefe_fefe_fefe_fefe; // clears column 0
+const NOT_FILE_H: u64 = 0x7f7f_7f7f_7f7f_7f7f; // clears column 7
+
+// Shift every disc one step in a direction, dropping discs that would wrap
+// around the edge of the board. Directions follow `game::DIRECTIONS`.
+#[inline]
+fn shift(bits: u64, dir: usize) -> u64 {
+    match dir {
+        0 => bits >> 8,                // N
+        1 => bits << 8,                // S
+        2 => (bits >> 1) & NOT_FILE_H, // W
+        3 => (bits << 1) & NOT_FILE_A, // E
+        4 => (bits >> 9) & NOT_FILE_H, // NW
+        5 => (bits >> 7) & NOT_FILE_A, // NE
+        6 => (bits << 7) & NOT_FILE_H, // SW
+        _ => (bits << 9) & NOT_FILE_A, // SE
+    }
+}
+
+// Bit index of (row, col)
+#[inline]
+pub fn square(row: usize, col: usize) -> usize {
+    row * SIZE + col
+}
+
+// (row, col) of a bit index
+#[inline]
+pub fn coords(square: usize) -> (usize, usize) {
+    (square / SIZE, square % SIZE)
+}
+
+// Iterate over the indices of the set bits, lowest first
+pub fn squares(mut bits: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if bits == 0 {
+            return None;
+        }
+        let square = bits.trailing_zeros() as usize;
+        bits &= bits - 1;
+        Some(square)
+    })
+}
+
+// Squares where the owner of `own` may move against `opp`. Blocked squares
+// stop a line like empty ones do, but callers must remove them from the
+// result, as `Bitboard::legal_moves` does.
+pub fn moves(own: u64, opp: u64) -> u64 {
+    let empty = !(own | opp);
+    let mut moves = 0;
+    for dir in 0..8 {
+        // A run of opponent discs can be at most six long
+        let mut run = shift(own, dir) & opp;
+        for _ in 0..5 {
+            run |= shift(run, dir) & opp;
+        }
+        moves |= shift(run, dir) & empty;
+    }
+    moves
+}
+
+// Discs captured when the owner of `own` plays at `square`. Returns 0 if
+// the move flips nothing. Whether `square` itself is empty is not checked.
+pub fn flips(own: u64, opp: u64, square: usize) -> u64 {
+    let placed = 1u64 << square;
+    let mut flipped = 0;
+    for dir in 0..8 {
+        let mut run = 0;
+        let mut cur = shift(placed, dir);
+        while cur & opp != 0 {
+            run |= cur;
+            cur = shift(cur, dir);
+        }
+        if cur & own != 0 {
+            flipped |= run;
+        }
+    }
+    flipped
+}
+
+// Number of symmetries of the square board: four rotations, each with or
+// without a reflection
+pub const SYMMETRIES: usize = 8;
+
+// Map a square through symmetry 0..8. Bit 0 of `symmetry` swaps rows and
+// columns, then bit 1 mirrors the rows and bit 2 mirrors the columns.
+// Symmetry 0 is the identity.
+#[inline]
+pub fn transform_square(square: usize, symmetry: usize) -> usize {
+    let (mut row, mut col) = coords(square);
+    if symmetry & 1 != 0 {
+        (row, col) = (col, row);
+    }
+    if symmetry & 2 != 0 {
+        row = SIZE - 1 - row;
+    }
+    if symmetry & 4 != 0 {
+        col = SIZE - 1 - col;
+    }
+    self::square(row, col)
+}
+
+// The symmetry that undoes `symmetry`. Undoing a swap followed by mirrors
+// means mirroring first, which is the swap with the mirrors exchanged.
+#[inline]
+pub fn inverse_symmetry(symmetry: usize) -> usize {
+    if symmetry & 1 == 0 {
+        symmetry
+    } else {
+        1 | (symmetry & 2) << 1 | (symmetry & 4) >> 1
+    }
+}
+
+// Map every set bit through a symmetry
+pub fn transform(bits: u64, symmetry: usize) -> u64 {
+    squares(bits).fold(0, |acc, sq| acc | 1 << transform_square(sq, symmetry))
+}
+
+// A position as one bit mask per colour, plus the squares no disc may
+// occupy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Bitboard {
+    pub black: u64,
+    pub white: u64,
+    pub blocked: u64,
+}
+
+impl Bitboard {
+    // The standard starting position
+    pub fn initial() -> Bitboard {
+        Bitboard::from_board(&crate::create_initial_board())
+    }
+
+    pub fn from_board(board: &Board) -> Bitboard {
+        Bitboard::from_grid(&crate::game::grid_from_board(board))
+    }
+
+    pub fn to_board(&self) -> Board {
+        crate::game::board_from_grid(&self.to_grid())
+    }
+
+    pub fn from_grid(grid: &Grid) -> Bitboard {
+        let mut bitboard = Bitboard::default();
+        for (r, row) in grid.iter().enumerate() {
+            for (c, &cell) in row.iter().enumerate() {
+                match cell {
+                    Cell::Black => bitboard.black |= 1 << square(r, c),
+                    Cell::White => bitboard.white |= 1 << square(r, c),
+                    Cell::Blocked => bitboard.blocked |= 1 << square(r, c),
+                    Cell::Empty => {}
+                }
+            }
+        }
+        bitboard
+    }
+
+    pub fn to_grid(&self) -> Grid {
+        let mut grid = [[Cell::Empty; SIZE]; SIZE];
+        for sq in squares(self.black) {
+            let (r, c) = coords(sq);
+            grid[r][c] = Cell::Black;
+        }
+        for sq in squares(self.white) {
+            let (r, c) = coords(sq);
+            grid[r][c] = Cell::White;
+        }
+        for sq in squares(self.blocked) {
+            let (r, c) = coords(sq);
+            grid[r][c] = Cell::Blocked;
+        }
+        grid
+    }
+
+    // The discs of one player
+    #[inline]
+    pub fn discs(&self, player: Player) -> u64 {
+        match player {
+            Player::Black => self.black,
+            Player::White => self.white,
+        }
+    }
+
+    // Squares where a disc may still be placed
+    #[inline]
+    pub fn empty(&self) -> u64 {
+        !(self.black | self.white | self.blocked)
+    }
+
+    // Legal moves for the player as a bit mask
+    #[inline]
+    pub fn legal_moves(&self, player: Player) -> u64 {
+        moves(self.discs(player), self.discs(player.opponent())) & !self.blocked
+    }
+
+    // Discs the player would flip by playing at `square`
+    #[inline]
+    pub fn flips(&self, player: Player, square: usize) -> u64 {
+        flips(self.discs(player), self.discs(player.opponent()), square)
+    }
+
+    // Place a disc for the player at `square` and flip the captured discs.
+    // Like `apply_move`, the move is not checked for legality. Returns the
+    // flipped discs.
+    pub fn apply(&mut self, player: Player, square: usize) -> u64 {
+        let flipped = self.flips(player, square);
+        let placed = 1u64 << square;
+        match player {
+            Player::Black => {
+                self.black |= placed | flipped;
+                self.white &= !(placed | flipped);
+            }
+            Player::White => {
+                self.white |= placed | flipped;
+                self.black &= !(placed | flipped);
+            }
+        }
+        flipped
+    }
+
+    // The position seen through a symmetry of the board
+    pub fn transform(&self, symmetry: usize) -> Bitboard {
+        Bitboard {
+            black: transform(self.black, symmetry),
+            white: transform(self.white, symmetry),
+            blocked: transform(self.blocked, symmetry),
+        }
+    }
+
+    // The representative of the position among its symmetric copies, and
+    // the symmetry that maps the position onto it. The representative is
+    // the copy with the smallest Zobrist hash with Black to move, as in
+    // `zobrist::canonical_hash`.
+    pub fn canonical(&self) -> (Bitboard, usize) {
+        (0..SYMMETRIES)
+            .map(|symmetry| (self.transform(symmetry), symmetry))
+            .min_by_key(|(image, _)| image.key(Player::Black))
+            .expect("there is always a symmetry")
+    }
+
+    // Zobrist hash of the position with `player` to move
+    pub fn key(&self, player: Player) -> u64 {
+        crate::zobrist::hash_bitboard(self, player)
+    }
+
+    // A hash shared by all symmetric copies of the position: the hash of
+    // its canonical copy
+    pub fn canonical_key(&self, player: Player) -> u64 {
+        self.canonical().0.key(player)
+    }
+
+    // Number of black and white discs
+    pub fn count(&self) -> (usize, usize) {
+        (self.black.count_ones() as usize, self.white.count_ones() as usize)
+    }
+}
@@ -0,0 +1,221 @@
+// An opening book: weighted moves for positions near the start of the game.
+//
+// Each position is stored once for all eight symmetries of the board, so a
+// line learned after f5 is also found after d3, c4 and e6. Book files have
+// one position per line in `notation` format followed by its moves in
+// transcript notation with their weights:
+//
+//   ...........................WB......BW........................... B f5:12 d3:4
+//
+// Blank lines and lines starting with '#' are ignored.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use rand::Rng;
+
+use crate::bitboard::{coords, inverse_symmetry, square, transform_square, Bitboard};
+use crate::game::Player;
+use crate::history::{parse_square_name, square_name, History, Move, TranscriptError};
+use crate::notation::{format_position, parse_position, PositionError};
+
+// Why a book or the games to build one from could not be read. Line and
+// game numbers start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BookError {
+    // The line does not start with a valid position
+    BadPosition { line: usize, error: PositionError },
+    // A move is not written as square:weight
+    BadMove { line: usize, text: String },
+    // The move cannot be played in the position
+    IllegalMove { line: usize, text: String },
+    // The weights of a move add up to more than a u32 holds
+    WeightOverflow { line: usize, text: String },
+    // A game transcript could not be replayed
+    BadGame { game: usize, error: TranscriptError },
+}
+
+impl fmt::Display for BookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookError::BadPosition { line, error } => write!(f, "line {}: {}", line, error),
+            BookError::BadMove { line, text } => {
+                write!(f, "line {}: '{}' is not a move with a weight, like f5:3", line, text)
+            }
+            BookError::IllegalMove { line, text } => {
+                write!(f, "line {}: '{}' is not a legal move", line, text)
+            }
+            BookError::WeightOverflow { line, text } => {
+                write!(f, "line {}: the weights for '{}' add up to more than {}", line, text, u32::MAX)
+            }
+            BookError::BadGame { game, error } => write!(f, "game {}: {}", game, error),
+        }
+    }
+}
+
+impl std::error::Error for BookError {}
+
+// A position as black discs, white discs, blocked squares and the side to
+// move
+type Key = (u64, u64, u64, Player);
+
+// The key of a position's canonical copy, and the symmetry that maps the
+// position onto it
+fn canonical(board: &Bitboard, player: Player) -> (Key, usize) {
+    let (image, symmetry) = board.canonical();
+    ((image.black, image.white, image.blocked, player), symmetry)
+}
+
+// Weighted moves for positions, keyed by their canonical form. Moves are
+// stored as squares of the canonical position.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Book {
+    entries: HashMap<Key, Vec<(usize, u32)>>,
+}
+
+impl Book {
+    pub fn new() -> Book {
+        Book::default()
+    }
+
+    // Number of positions in the book
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Add `weight` to a move in a position; totals stop at u32::MAX. The
+    // move is not checked.
+    pub fn add(&mut self, board: &Bitboard, player: Player, sq: usize, weight: u32) {
+        let (key, symmetry) = canonical(board, player);
+        let sq = transform_square(sq, symmetry);
+        let moves = self.entries.entry(key).or_default();
+        match moves.iter_mut().find(|(known, _)| *known == sq) {
+            Some((_, total)) => *total = total.saturating_add(weight),
+            None => moves.push((sq, weight)),
+        }
+    }
+
+    // The book moves for a position as (square, weight), heaviest first
+    pub fn moves(&self, board: &Bitboard, player: Player) -> Vec<(usize, u32)> {
+        let (key, symmetry) = canonical(board, player);
+        let inverse = inverse_symmetry(symmetry);
+        let mut moves: Vec<(usize, u32)> = self
+            .entries
+            .get(&key)
+            .map(|moves| moves.iter().map(|&(sq, weight)| (transform_square(sq, inverse), weight)).collect())
+            .unwrap_or_default();
+        moves.sort_by_key(|&(sq, weight)| (std::cmp::Reverse(weight), sq));
+        moves
+    }
+
+    // Pick a legal book move at random, in proportion to the weights.
+    // Returns None if the position is not in the book.
+    pub fn choose(&self, board: &Bitboard, player: Player, rng: &mut impl Rng) -> Option<usize> {
+        let legal = board.legal_moves(player);
+        let moves: Vec<(usize, u32)> = self
+            .moves(board, player)
+            .into_iter()
+            .filter(|&(sq, weight)| legal & (1 << sq) != 0 && weight > 0)
+            .collect();
+        // Up to 60 u32 weights always fit in a u64
+        let total: u64 = moves.iter().map(|&(_, weight)| u64::from(weight)).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut pick = rng.gen_range(0..total);
+        for (sq, weight) in moves {
+            let weight = u64::from(weight);
+            if pick < weight {
+                return Some(sq);
+            }
+            pick -= weight;
+        }
+        unreachable!("the pick is below the total weight")
+    }
+
+    // Count every move in the first `plies` moves of a game, passes aside
+    pub fn add_game(&mut self, history: &History, plies: usize) {
+        let mut game = history.start().clone();
+        for &mv in history.moves().iter().take(plies) {
+            if let Move::Play(row, col) = mv {
+                self.add(&Bitboard::from_grid(game.grid()), game.to_move(), square(row, col), 1);
+            }
+            match mv {
+                Move::Play(row, col) => game.play(row, col),
+                Move::Pass => game.pass(),
+            };
+        }
+    }
+
+    // Build a book from game transcripts in standard notation, counting the
+    // first `plies` moves of each
+    pub fn from_transcripts<'a>(transcripts: impl IntoIterator<Item = &'a str>, plies: usize) -> Result<Book, BookError> {
+        let mut book = Book::new();
+        for (i, transcript) in transcripts.into_iter().enumerate() {
+            let history = History::from_transcript(transcript)
+                .map_err(|error| BookError::BadGame { game: i + 1, error })?;
+            book.add_game(&history, plies);
+        }
+        Ok(book)
+    }
+
+    // Read a book in the file format described above
+    pub fn parse(text: &str) -> Result<Book, BookError> {
+        let mut book = Book::new();
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let position = format!("{} {}", words.next().unwrap_or(""), words.next().unwrap_or(""));
+            let (board, player) = parse_position(&position)
+                .map_err(|error| BookError::BadPosition { line: line_number, error })?;
+            let board = Bitboard::from_board(&board);
+
+            for word in words {
+                let bad_move = || BookError::BadMove { line: line_number, text: word.to_string() };
+                let (name, weight) = word.split_once(':').ok_or_else(bad_move)?;
+                let (row, col) = parse_square_name(name).ok_or_else(bad_move)?;
+                let weight: u32 = weight.parse().map_err(|_| bad_move())?;
+                if board.legal_moves(player) & (1 << square(row, col)) == 0 {
+                    return Err(BookError::IllegalMove { line: line_number, text: name.to_string() });
+                }
+                // The move may already have a weight from an earlier line
+                let known = book.moves(&board, player).into_iter().find(|&(sq, _)| sq == square(row, col));
+                if known.map_or(0, |(_, total)| total).checked_add(weight).is_none() {
+                    return Err(BookError::WeightOverflow { line: line_number, text: name.to_string() });
+                }
+                book.add(&board, player, square(row, col), weight);
+            }
+        }
+        Ok(book)
+    }
+}
+
+// The book in its file format, one canonical position per line, sorted so
+// the same book always gives the same text
+impl fmt::Display for Book {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut keys: Vec<&Key> = self.entries.keys().collect();
+        keys.sort_by_key(|&&(black, white, blocked, player)| {
+            (black.count_ones() + white.count_ones(), black, white, blocked, player == Player::White)
+        });
+        for &key in keys {
+            let (black, white, blocked, player) = key;
+            let board = Bitboard { black, white, blocked };
+            write!(f, "{}", format_position(&board.to_board(), player))?;
+            for (sq, weight) in self.moves(&board, player) {
+                let (row, col) = coords(sq);
+                write!(f, " {}:{}", square_name(row, col), weight)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
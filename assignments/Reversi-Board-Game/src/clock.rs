@@ -0,0 +1,206 @@
+// Game clocks. Each player has a store of time that runs down during their
+// turns; a player whose time runs out loses the game.
+//
+// Time controls are written as the starting time, optionally followed by
+// '+' and an increment added after every move: "5m" is five minutes sudden
+// death, "3m+2s" is three minutes plus two seconds a move. A number without
+// a unit is minutes before the '+' and seconds after it, so "3+2" is the
+// same as "3m+2s".
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::game::Player;
+
+// How much time each player gets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControl {
+    pub initial: Duration,
+    // Added to a player's clock after each of their moves; zero for sudden
+    // death
+    pub increment: Duration,
+}
+
+// Why a time control could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeControlError {
+    pub text: String,
+}
+
+impl fmt::Display for TimeControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' is not a time control, like 5m or 3m+2s", self.text)
+    }
+}
+
+impl std::error::Error for TimeControlError {}
+
+// A duration like "90s", "1.5m" or a bare number in `unit` seconds
+fn parse_duration(text: &str, unit: f64) -> Option<Duration> {
+    let (number, scale) = match text.strip_suffix('m') {
+        Some(number) => (number, 60.0),
+        None => match text.strip_suffix('s') {
+            Some(number) => (number, 1.0),
+            None => (text, unit),
+        },
+    };
+    let value: f64 = number.parse().ok()?;
+    // Negative, infinite and too large values do not make a duration
+    Duration::try_from_secs_f64(value * scale).ok()
+}
+
+// Whole minutes as "5m", anything else as seconds
+fn format_control_duration(duration: Duration) -> String {
+    let secs = duration.as_secs_f64();
+    if duration.subsec_nanos() == 0 && duration.as_secs().is_multiple_of(60) {
+        format!("{}m", duration.as_secs() / 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+impl TimeControl {
+    pub fn sudden_death(initial: Duration) -> TimeControl {
+        TimeControl { initial, increment: Duration::ZERO }
+    }
+
+    // Read a time control in the format described above
+    pub fn parse(text: &str) -> Result<TimeControl, TimeControlError> {
+        let error = || TimeControlError { text: text.to_string() };
+        let trimmed = text.trim();
+        let (initial, increment) = match trimmed.split_once('+') {
+            Some((initial, increment)) => (initial, Some(increment)),
+            None => (trimmed, None),
+        };
+        let initial = parse_duration(initial.trim(), 60.0).filter(|initial| !initial.is_zero()).ok_or_else(error)?;
+        let increment = match increment {
+            Some(increment) => parse_duration(increment.trim(), 1.0).ok_or_else(error)?,
+            None => Duration::ZERO,
+        };
+        Ok(TimeControl { initial, increment })
+    }
+}
+
+// The time control in the format `parse` reads
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format_control_duration(self.initial))?;
+        if !self.increment.is_zero() {
+            write!(f, "+{}", format_control_duration(self.increment))?;
+        }
+        Ok(())
+    }
+}
+
+// Time shown on a clock: "4:05", with tenths of a second under ten seconds
+// ("0:07.3")
+pub fn format_clock(time: Duration) -> String {
+    let secs = time.as_secs();
+    if secs < 10 {
+        format!("0:0{}.{}", secs, time.subsec_millis() / 100)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+fn index(player: Player) -> usize {
+    match player {
+        Player::Black => 0,
+        Player::White => 1,
+    }
+}
+
+// Both players' clocks. At most one runs at a time, from `start` until
+// `stop`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clock {
+    control: TimeControl,
+    remaining: [Duration; 2],
+    running: Option<(Player, Instant)>,
+    // The player whose time ran out, if any
+    flagged: Option<Player>,
+}
+
+impl Clock {
+    // Both clocks set to the starting time, neither running
+    pub fn new(control: TimeControl) -> Clock {
+        Clock { control, remaining: [control.initial; 2], running: None, flagged: None }
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    // Time `player` has left, counting the turn in progress
+    pub fn remaining(&self, player: Player) -> Duration {
+        let stored = self.remaining[index(player)];
+        match self.running {
+            Some((running, since)) if running == player => stored.saturating_sub(since.elapsed()),
+            _ => stored,
+        }
+    }
+
+    // The player whose clock is running
+    pub fn running(&self) -> Option<Player> {
+        self.running.map(|(player, _)| player)
+    }
+
+    // The player who has run out of time, including one whose turn is
+    // still in progress
+    pub fn flagged(&self) -> Option<Player> {
+        self.flagged.or_else(|| self.running().filter(|&player| self.remaining(player).is_zero()))
+    }
+
+    // Start `player`'s clock. Does nothing if it is already running.
+    pub fn start(&mut self, player: Player) {
+        if self.running() != Some(player) {
+            self.stop();
+            self.running = Some((player, Instant::now()));
+        }
+    }
+
+    // Stop the running clock at the end of a move, charging the time taken.
+    // Returns false if the player ran out of time.
+    pub fn stop(&mut self) -> bool {
+        match self.running.take() {
+            Some((player, since)) => self.charge(player, since.elapsed()),
+            None => true,
+        }
+    }
+
+    // Take `elapsed` off `player`'s clock for a move and add the increment.
+    // If the time runs out the clock stays at zero, the player is flagged
+    // and false is returned.
+    pub fn charge(&mut self, player: Player, elapsed: Duration) -> bool {
+        let remaining = &mut self.remaining[index(player)];
+        if elapsed >= *remaining {
+            *remaining = Duration::ZERO;
+            self.flagged.get_or_insert(player);
+            return false;
+        }
+        *remaining = *remaining - elapsed + self.control.increment;
+        true
+    }
+}
+
+// Both clocks, as shown after every board in a timed game
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Clock: Black {}  White {}",
+            format_clock(self.remaining(Player::Black)),
+            format_clock(self.remaining(Player::White))
+        )
+    }
+}
+
+// Time to spend on one move with `remaining` on the clock, `increment`
+// added afterwards and `empties` empty squares on the board. The time is
+// spread over the mover's share of the moves left, and a reserve is kept so
+// the clock never runs down to zero.
+pub fn budget(remaining: Duration, increment: Duration, empties: u32) -> Duration {
+    let moves_left = empties.div_ceil(2).max(1);
+    let share = remaining / (moves_left + 1) + increment * 3 / 4;
+    share.min(remaining / 2)
+}
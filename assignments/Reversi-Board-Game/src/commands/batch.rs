@@ -0,0 +1,114 @@
+// Non-interactive play for scripts. Reads moves in RowCol form (like "cd")
+// separated by whitespace or commas, from a file or stdin. Lines starting
+// with '#' are comments. Every resulting position is written to stdout as
+// one line of JSON:
+//
+//   {"black":4,"board":["........",...],"move":"cd","player":"B","ply":1,"to_move":"W","white":1}
+//
+// Ply 0 is the starting position. A side without a valid move passes
+// automatically ("move":"pass"); "pass" may also be written explicitly.
+// The last line is the result:
+//
+//   {"result":{"black":40,"finished":true,"white":24,"winner":"B"}}
+//
+// The first bad or illegal move is reported as {"error":...} with the
+// specific "reason", and the process exits with status 1.
+
+use reversi::{count_pieces, create_initial_board, format_move, has_valid_moves, try_apply_move, try_parse_input, Board};
+
+use clap::ArgMatches;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+fn opponent(player: char) -> char {
+    if player == 'B' { 'W' } else { 'B' }
+}
+
+// The record for a position. `played` is the player and move that led to
+// it, absent for the starting position.
+fn position(ply: usize, played: Option<(char, &str)>, board: &Board, to_move: char) -> Value {
+    let (black, white) = count_pieces(board);
+    let rows: Vec<String> = board.iter().map(|row| row.iter().collect()).collect();
+    let mut record = json!({
+        "ply": ply,
+        "board": rows,
+        "black": black,
+        "white": white,
+        "to_move": to_move.to_string(),
+    });
+    if let Some((player, mv)) = played {
+        record["player"] = json!(player.to_string());
+        record["move"] = json!(mv);
+    }
+    record
+}
+
+// Print an error record and stop
+fn fail(ply: usize, player: char, mv: &str, error: &str, reason: &str) -> ! {
+    println!("{}", json!({ "error": error, "reason": reason, "ply": ply, "player": player.to_string(), "move": mv }));
+    process::exit(1);
+}
+
+// Play the moves from the file (or stdin) and print each position as JSON
+pub fn run(matches: &ArgMatches) {
+    let input = match matches.get_one::<String>("file").map(String::as_str) {
+        None | Some("-") => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text).map(|_| text)
+        }
+        Some(path) => fs::read_to_string(path),
+    }
+    .unwrap_or_else(|err| {
+        eprintln!("Failed to read moves: {}", err);
+        process::exit(1);
+    });
+
+    let tokens = input
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+        .filter(|token| !token.is_empty());
+
+    let mut board = create_initial_board();
+    let mut player = 'B';
+    let mut ply = 0;
+    println!("{}", position(0, None, &board, player));
+
+    for token in tokens {
+        let game_over = !has_valid_moves(&board, 'B') && !has_valid_moves(&board, 'W');
+        if game_over {
+            fail(ply + 1, player, token, "game is over", "neither player has a valid move");
+        }
+        // Pass for a side that cannot move, unless the pass is written out
+        if !has_valid_moves(&board, player) {
+            ply += 1;
+            println!("{}", position(ply, Some((player, "pass")), &board, opponent(player)));
+            player = opponent(player);
+            if token.eq_ignore_ascii_case("pass") {
+                continue;
+            }
+        }
+
+        ply += 1;
+        if token.eq_ignore_ascii_case("pass") {
+            fail(ply, player, token, "pass with a valid move", &format!("{} has a valid move", player));
+        }
+        let (row, col) = try_parse_input(token).unwrap_or_else(|err| fail(ply, player, token, "invalid input", &err.to_string()));
+        if let Err(err) = try_apply_move(&mut board, player, row, col) {
+            fail(ply, player, &format_move(row, col), "illegal move", &err.to_string());
+        }
+        println!("{}", position(ply, Some((player, &format_move(row, col))), &board, opponent(player)));
+        player = opponent(player);
+    }
+
+    let (black, white) = count_pieces(&board);
+    let winner = match black.cmp(&white) {
+        std::cmp::Ordering::Greater => json!("B"),
+        std::cmp::Ordering::Less => json!("W"),
+        std::cmp::Ordering::Equal => Value::Null,
+    };
+    let finished = !has_valid_moves(&board, 'B') && !has_valid_moves(&board, 'W');
+    println!("{}", json!({ "result": { "black": black, "white": white, "winner": winner, "finished": finished } }));
+}
@@ -0,0 +1,87 @@
+use reversi::bitboard::coords;
+use reversi::engine::SearchResult;
+use reversi::{format_move, Bitboard, Engine, Evaluation, History, Limit};
+
+use clap::ArgMatches;
+use std::process;
+use std::time::{Duration, Instant};
+
+// Middle-game positions reached by these transcripts are searched in turn
+const POSITIONS: [&str; 6] = [
+    "",
+    "f5d6c3d3c4f4f6f3e6e7d7",
+    "f5f6e6f4e3c5c4e7",
+    "f5d6c5f4e3f6",
+    "c4e3f6e6f5c5f4g6f7d3",
+    "f5f6e6f4g6c5f3g4e3d6c4d3",
+];
+
+fn timed(engine: &mut Engine<Evaluation>, board: &Bitboard, history: &History) -> (SearchResult, Duration) {
+    let start = Instant::now();
+    let result = engine.search(board, history.game().to_move());
+    (result, start.elapsed())
+}
+
+fn nps(nodes: u64, elapsed: Duration) -> f64 {
+    nodes as f64 / elapsed.as_secs_f64().max(1e-9)
+}
+
+// Search the benchmark positions with one thread and with several, report
+// nodes per second for both and check that they agree on every move
+pub fn run(matches: &ArgMatches) {
+    let depth = *matches.get_one::<u32>("depth").unwrap();
+    let mut single = Engine::new(super::evaluation(matches), Limit::Depth(depth));
+    let mut parallel = single.clone();
+    parallel.set_threads(*matches.get_one::<usize>("threads").unwrap());
+    let threads = parallel.threads();
+
+    println!("Depth {}, 1 thread against {} threads", depth, threads);
+    println!("{:>3}  {:>4}  {:>12} {:>12}  {:>12} {:>12}", "#", "Move", "Nodes", "N/s", "Nodes", "N/s");
+    let mut totals = [(0, Duration::ZERO); 2];
+    let mut agree = true;
+    for (i, transcript) in POSITIONS.iter().enumerate() {
+        let history = History::from_transcript(transcript).expect("benchmark transcripts are legal");
+        let board = Bitboard::from_grid(history.game().grid());
+        let (one, one_time) = timed(&mut single, &board, &history);
+        let (many, many_time) = timed(&mut parallel, &board, &history);
+        totals[0] = (totals[0].0 + one.nodes, totals[0].1 + one_time);
+        totals[1] = (totals[1].0 + many.nodes, totals[1].1 + many_time);
+
+        let name = one.best_move.map_or("pass".to_string(), |sq| {
+            let (row, col) = coords(sq);
+            format_move(row, col)
+        });
+        let same = (one.best_move, one.score) == (many.best_move, many.score);
+        agree &= same;
+        println!(
+            "{:>3}  {:>4}  {:>12} {:>12.0}  {:>12} {:>12.0}{}",
+            i + 1,
+            name,
+            one.nodes,
+            nps(one.nodes, one_time),
+            many.nodes,
+            nps(many.nodes, many_time),
+            if same { "" } else { "  DIFFERENT" }
+        );
+    }
+
+    println!(
+        "{:>3}  {:>4}  {:>12} {:>12.0}  {:>12} {:>12.0}",
+        "",
+        "All",
+        totals[0].0,
+        nps(totals[0].0, totals[0].1),
+        totals[1].0,
+        nps(totals[1].0, totals[1].1)
+    );
+    println!(
+        "Time: {:.3}s against {:.3}s, {:.2}x faster",
+        totals[0].1.as_secs_f64(),
+        totals[1].1.as_secs_f64(),
+        totals[0].1.as_secs_f64() / totals[1].1.as_secs_f64().max(1e-9)
+    );
+    if !agree {
+        eprintln!("Parallel search disagreed with the single thread.");
+        process::exit(1);
+    }
+}
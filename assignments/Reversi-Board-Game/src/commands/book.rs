@@ -0,0 +1,47 @@
+use reversi::Book;
+
+use clap::ArgMatches;
+use std::fs;
+use std::process;
+
+// Build a book from a file of transcripts, one game per line, and write it
+// out. Blank lines and lines starting with '#' are skipped.
+pub fn build(matches: &ArgMatches) {
+    let path = matches.get_one::<String>("games").unwrap();
+    let plies = *matches.get_one::<usize>("plies").unwrap();
+    let text = fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("Failed to read {}: {}", path, err);
+        process::exit(1);
+    });
+    let games = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    let book = Book::from_transcripts(games, plies).unwrap_or_else(|err| {
+        eprintln!("Invalid game in {}: {}", path, err);
+        process::exit(1);
+    });
+
+    match matches.get_one::<String>("output") {
+        Some(output) => {
+            if let Err(err) = fs::write(output, book.to_string()) {
+                eprintln!("Failed to write {}: {}", output, err);
+                process::exit(1);
+            }
+            eprintln!("Wrote {} positions to {}", book.len(), output);
+        }
+        None => print!("{}", book),
+    }
+}
+
+// Read a book file. Exits with a message if the file cannot be used.
+pub fn load(path: &str) -> Book {
+    let text = fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("Failed to read {}: {}", path, err);
+        process::exit(1);
+    });
+    Book::parse(&text).unwrap_or_else(|err| {
+        eprintln!("Invalid book in {}: {}", path, err);
+        process::exit(1);
+    })
+}
@@ -0,0 +1,116 @@
+use reversi::database::Outcomes;
+use reversi::history::square_name;
+use reversi::{print_board, Database, Game, History, Move, Player};
+
+use clap::ArgMatches;
+use std::fs;
+use std::io;
+use std::process;
+
+// Read a database file; a file that does not exist yet is an empty
+// database. Exits with a message if the file cannot be used.
+fn load(path: &str) -> Database {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path, err);
+            process::exit(1);
+        }
+    };
+    Database::parse(&text).unwrap_or_else(|err| {
+        eprintln!("Invalid database {}: {}", path, err);
+        process::exit(1);
+    })
+}
+
+// Add the games in transcript files to a database file, creating it if
+// needed. Nothing is written if any game is invalid.
+pub fn add(matches: &ArgMatches) {
+    let path = matches.get_one::<String>("database").unwrap();
+    let mut database = load(path);
+    let mut added = 0;
+    for games in matches.get_many::<String>("games").unwrap() {
+        let text = fs::read_to_string(games).unwrap_or_else(|err| {
+            eprintln!("Failed to read {}: {}", games, err);
+            process::exit(1);
+        });
+        added += database.add_text(&text).unwrap_or_else(|err| {
+            eprintln!("Invalid game in {}: {}", games, err);
+            process::exit(1);
+        });
+    }
+
+    if let Err(err) = fs::write(path, database.to_string()) {
+        eprintln!("Failed to write {}: {}", path, err);
+        process::exit(1);
+    }
+    eprintln!("Added {} games to {} ({} games, {} positions)", added, path, database.len(), database.positions());
+}
+
+// A share of games as a percentage, or "-" without any finished games
+fn percent(rate: Option<f64>) -> String {
+    rate.map_or("-".to_string(), |rate| format!("{:.1}%", 100.0 * rate))
+}
+
+fn summary(outcomes: &Outcomes) -> String {
+    let mut text = format!(
+        "{} games: Black {}, White {}, draws {}",
+        outcomes.games(),
+        percent(outcomes.win_rate(Player::Black)),
+        percent(outcomes.win_rate(Player::White)),
+        percent(outcomes.draw_rate())
+    );
+    if outcomes.unfinished > 0 {
+        text += &format!(" ({} unfinished)", outcomes.unfinished);
+    }
+    text
+}
+
+// Show how the games in a database went on from a position
+pub fn query(matches: &ArgMatches) {
+    let database = load(matches.get_one::<String>("database").unwrap());
+    let start = match matches.get_one::<String>("position") {
+        Some(text) => Game::from_position(text).unwrap_or_else(|err| {
+            eprintln!("Invalid position: {}", err);
+            process::exit(1);
+        }),
+        None => Game::new(),
+    };
+    let transcript = matches.get_one::<String>("moves").map_or("", String::as_str);
+    let history = History::from_transcript_at(start, transcript).unwrap_or_else(|err| {
+        eprintln!("Invalid moves: {}", err);
+        process::exit(1);
+    });
+    let game = history.game();
+    print_board(&game.board());
+
+    let stats = database.stats(game);
+    if stats.outcomes.games() == 0 {
+        println!("No games in the database reach this position.");
+        return;
+    }
+    println!("{}", summary(&stats.outcomes));
+    if stats.continuations.is_empty() {
+        return;
+    }
+
+    let top = matches.get_one::<u32>("top").map_or(usize::MAX, |&top| top as usize);
+    println!();
+    println!("{:<5} {:>6}  {:>6}  {:>6}  {:>6}", "Move", "Games", "Black", "White", "Draws");
+    for continuation in stats.continuations.iter().take(top) {
+        let name = match continuation.mv {
+            Move::Play(row, col) => square_name(row, col),
+            Move::Pass => "pass".to_string(),
+        };
+        let outcomes = &continuation.outcomes;
+        println!(
+            "{:<5} {:>6}  {:>6}  {:>6}  {:>6}",
+            name,
+            outcomes.games(),
+            percent(outcomes.win_rate(Player::Black)),
+            percent(outcomes.win_rate(Player::White)),
+            percent(outcomes.draw_rate())
+        );
+    }
+}
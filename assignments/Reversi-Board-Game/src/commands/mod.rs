@@ -0,0 +1,62 @@
+// Subcommands of the reversi binary besides the interactive game
+pub mod analyze;
+pub mod batch;
+pub mod bench;
+pub mod book;
+pub mod database;
+pub mod network;
+pub mod perft;
+pub mod review;
+pub mod solve;
+pub mod train;
+pub mod tui;
+
+use reversi::pattern::PatternEval;
+use reversi::{parse_position, Board, Evaluation, Player};
+
+use clap::ArgMatches;
+use std::fs;
+use std::process;
+
+// Read a position file in the position notation (see `reversi::notation`).
+// Lines starting with '#' are comments, unless they hold nothing but squares
+// and sides, as a row that starts with a blocked square does. Exits with a
+// message if the file cannot be used.
+pub fn read_position(path: &str) -> (Board, Player) {
+    let text = fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("Failed to read {}: {}", path, err);
+        process::exit(1);
+    });
+    let text: String = text.lines().filter(|line| !is_comment(line)).collect::<Vec<_>>().join("\n");
+    parse_position(&text).unwrap_or_else(|err| {
+        eprintln!("Invalid position in {}: {}", path, err);
+        process::exit(1);
+    })
+}
+
+fn is_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with('#') && !line.chars().all(|c| c.is_whitespace() || matches!(c, 'B' | 'W' | '.' | '#'))
+}
+
+// Read a pattern weights file written by the `tune` subcommand. Exits with
+// a message if the file cannot be used.
+pub fn read_weights(path: &str) -> PatternEval {
+    let text = fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("Failed to read {}: {}", path, err);
+        process::exit(1);
+    });
+    PatternEval::parse(&text).unwrap_or_else(|err| {
+        eprintln!("Invalid weights in {}: {}", path, err);
+        process::exit(1);
+    })
+}
+
+// The evaluation chosen with --weights: the pattern weights in that file,
+// or the default heuristic
+pub fn evaluation(matches: &ArgMatches) -> Evaluation {
+    match matches.get_one::<String>("weights") {
+        Some(path) => Evaluation::Pattern(read_weights(path)),
+        None => Evaluation::default(),
+    }
+}
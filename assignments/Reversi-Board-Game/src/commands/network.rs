@@ -0,0 +1,107 @@
+use reversi::net::{self, Client, ClientMessage, Outcome, ServerMessage};
+use reversi::{format_move, print_board, print_winner, try_parse_input, Game};
+
+use clap::ArgMatches;
+use std::io::{self, Write};
+use std::net::TcpListener;
+use std::process;
+
+// Host one game and report how it ended
+pub fn serve(matches: &ArgMatches) {
+    let addr = matches.get_one::<String>("addr").unwrap();
+    let listener = TcpListener::bind(addr).unwrap_or_else(|err| {
+        eprintln!("Failed to listen on {}: {}", addr, err);
+        process::exit(1);
+    });
+    println!("Waiting for two players on {}...", listener.local_addr().unwrap());
+
+    match net::serve(&listener) {
+        Ok(Outcome::Finished(black, white)) => println!("Game over: Black {} - White {}", black, white),
+        Ok(Outcome::Abandoned(player)) => println!("{} left the game.", player.name()),
+        Err(err) => {
+            eprintln!("Server error: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+// Join a game on a server and play it from this terminal
+pub fn connect(matches: &ArgMatches) {
+    let addr = matches.get_one::<String>("addr").unwrap();
+    let name = matches.get_one::<String>("name").unwrap();
+    let mut client = Client::connect(addr).unwrap_or_else(|err| {
+        eprintln!("Failed to connect to {}: {}", addr, err);
+        process::exit(1);
+    });
+    if let Err(err) = play(&mut client, name) {
+        eprintln!("Connection error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn play(client: &mut Client, name: &str) -> io::Result<()> {
+    client.send(&ClientMessage::Join(name.to_string()))?;
+    let mut me = None;
+    let mut game = Game::new();
+
+    loop {
+        match client.recv()? {
+            ServerMessage::Welcome(player) => {
+                println!("You are playing {}. Waiting for an opponent...", player.name());
+                me = Some(player);
+            }
+            ServerMessage::Start => println!("Game started."),
+            ServerMessage::Board(position) => {
+                game = Game::from_position(&position)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                print_board(&game.board());
+                if Some(game.to_move()) == me {
+                    take_turn(client, &game)?;
+                }
+            }
+            ServerMessage::Moved(player, row, col) => {
+                println!("{} played {}.", player.name(), format_move(row, col));
+            }
+            ServerMessage::Passed(player) => println!("{} passed.", player.name()),
+            ServerMessage::Error(reason) => {
+                println!("Rejected: {}", reason);
+                if Some(game.to_move()) == me {
+                    take_turn(client, &game)?;
+                }
+            }
+            ServerMessage::Left(player) => {
+                println!("{} left the game.", player.name());
+                return Ok(());
+            }
+            ServerMessage::GameOver(_, _) => {
+                print_winner(&game.board());
+                return Ok(());
+            }
+        }
+    }
+}
+
+// Ask the user for a command until one can be sent to the server
+fn take_turn(client: &mut Client, game: &Game) -> io::Result<()> {
+    if game.must_pass() {
+        println!("{} player has no valid move.", game.to_move());
+        return client.send(&ClientMessage::Pass);
+    }
+    loop {
+        print!("Enter move for colour {} (RowCol, or quit): ", game.to_move());
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input)? == 0 {
+            return client.send(&ClientMessage::Quit);
+        }
+        let input = input.trim();
+        if input == "quit" {
+            return client.send(&ClientMessage::Quit);
+        }
+        match try_parse_input(input) {
+            Ok((row, col)) => return client.send(&ClientMessage::Move(row, col)),
+            Err(err) => println!("Invalid input: {}. Try again.", err),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use reversi::perft::{divide, perft, perft_board, START_COUNTS};
+use reversi::notation::initial_position;
+use reversi::{create_initial_board, format_move, parse_position, print_board, Bitboard, Move, Player};
+
+use clap::ArgMatches;
+use std::process;
+use std::time::Instant;
+
+// Count leaf nodes for every depth up to the one asked for. From the
+// standard start the counts are checked against the known values and any
+// difference makes the command fail.
+pub fn run(matches: &ArgMatches) {
+    let depth = *matches.get_one::<u32>("depth").unwrap();
+    let text = matches.get_one::<String>("position").cloned().unwrap_or_else(initial_position);
+    let (board, player) = parse_position(&text).unwrap_or_else(|err| {
+        eprintln!("Invalid position: {}", err);
+        process::exit(1);
+    });
+    let from_start = board == create_initial_board() && player == Player::Black;
+    let slow = matches.get_flag("slow");
+    print_board(&board);
+
+    let bitboard = Bitboard::from_board(&board);
+    let mut failed = false;
+    for d in 1..=depth {
+        let start = Instant::now();
+        let count = if slow {
+            perft_board(&board, player.to_char(), d)
+        } else {
+            perft(&bitboard, player, d)
+        };
+        let elapsed = start.elapsed().as_secs_f64();
+        print!("Depth {}: {} leaves in {:.3}s", d, count, elapsed);
+        match START_COUNTS.get(d as usize - 1) {
+            Some(&expected) if from_start && expected != count => {
+                print!(" MISMATCH, expected {}", expected);
+                failed = true;
+            }
+            Some(_) if from_start => print!(" ok"),
+            _ => {}
+        }
+        println!();
+    }
+
+    if matches.get_flag("divide") {
+        for (mv, count) in divide(&bitboard, player, depth) {
+            match mv {
+                Move::Play(row, col) => println!("{}: {}", format_move(row, col), count),
+                Move::Pass => println!("pass: {}", count),
+            }
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,95 @@
+// Review a recorded game move by move:
+//
+//    No.  Player  Move      Score  Best  Best score    Loss
+//      1  Black   f5          -10  d3           -10       0
+//      2  White   f6          -52  d6           -25      27  inaccuracy
+//
+// followed by each player's count of inaccuracies, mistakes and blunders
+// and the annotated transcript. Scores are shown as in `analyze`.
+
+use reversi::engine::WIN_SCORE;
+use reversi::history::square_name;
+use reversi::review::{annotated_transcript, review, tally, MoveReview, Verdict};
+use reversi::{Engine, Game, Limit, Player};
+
+use super::analyze::format_score;
+use crate::load_transcript;
+
+use clap::ArgMatches;
+use std::process;
+
+fn name(square: (usize, usize)) -> String {
+    square_name(square.0, square.1)
+}
+
+// A loss big enough to turn a win into a draw or a loss is shown as such
+// rather than in evaluation units
+fn format_loss(review: &MoveReview) -> String {
+    if review.loss() >= WIN_SCORE / 2 {
+        "result".to_string()
+    } else {
+        review.loss().to_string()
+    }
+}
+
+fn count(count: usize, one: &str, many: &str) -> String {
+    format!("{} {}", count, if count == 1 { one } else { many })
+}
+
+// Replay the game in a record written with --record, search every position
+// where the mover had a choice and print the report described above
+pub fn run(matches: &ArgMatches) {
+    let start = match matches.get_one::<String>("position") {
+        Some(text) => Game::from_position(text).unwrap_or_else(|err| {
+            eprintln!("Invalid position: {}", err);
+            process::exit(1);
+        }),
+        None => Game::new(),
+    };
+    let history = load_transcript(start, matches.get_one::<String>("record").unwrap());
+    let depth = *matches.get_one::<u32>("depth").unwrap();
+    let mut engine = Engine::new(super::evaluation(matches), Limit::Depth(depth));
+    engine.set_threads(*matches.get_one::<usize>("threads").unwrap());
+
+    let reviews = review(&history, &engine, |done, total| eprint!("\rReviewed {}/{} moves", done, total));
+    eprintln!();
+    if reviews.is_empty() {
+        println!("The record has no moves to review.");
+        return;
+    }
+
+    println!("{:>4}  {:<6}  {:<4}  {:>9}  {:<4}  {:>10}  {:>6}", "No.", "Player", "Move", "Score", "Best", "Best score", "Loss");
+    for review in &reviews {
+        let verdict = match review.verdict() {
+            Verdict::Best | Verdict::Good => String::new(),
+            verdict => format!("  {}", verdict),
+        };
+        let note = if review.choices == 1 { "  only move" } else { "" };
+        println!(
+            "{:>4}  {:<6}  {:<4}  {:>9}  {:<4}  {:>10}  {:>6}{}{}",
+            review.number,
+            review.player.name(),
+            name(review.played),
+            format_score(review.played_score),
+            name(review.best),
+            format_score(review.best_score),
+            format_loss(review),
+            verdict,
+            note
+        );
+    }
+
+    println!();
+    for player in [Player::Black, Player::White] {
+        let counts = tally(&reviews, player);
+        println!(
+            "{}: {}, {}, {}",
+            player.name(),
+            count(counts[Verdict::Inaccuracy as usize], "inaccuracy", "inaccuracies"),
+            count(counts[Verdict::Mistake as usize], "mistake", "mistakes"),
+            count(counts[Verdict::Blunder as usize], "blunder", "blunders")
+        );
+    }
+    println!();
+    println!("{}", annotated_transcript(&reviews));
+}
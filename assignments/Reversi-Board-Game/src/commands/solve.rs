@@ -0,0 +1,40 @@
+use reversi::bitboard::{coords, Bitboard};
+use reversi::{format_move, print_board, Solver};
+
+use clap::ArgMatches;
+use std::time::Instant;
+
+use super::read_position;
+
+// Solve the position in a file exactly and print the best move and result
+pub fn run(matches: &ArgMatches) {
+    let path = matches.get_one::<String>("file").unwrap();
+    let (board, player) = read_position(path);
+    print_board(&board);
+
+    let bitboard = Bitboard::from_board(&board);
+    let empties = bitboard.empty().count_ones();
+    println!("Solving for colour {} with {} empty squares...", player, empties);
+
+    let start = Instant::now();
+    let solution = Solver::new().solve(&bitboard, player);
+    let elapsed = start.elapsed();
+
+    match solution.best_move {
+        Some(sq) => {
+            let (row, col) = coords(sq);
+            println!("Best move: {}", format_move(row, col));
+        }
+        None if bitboard.legal_moves(player.opponent()) == 0 => println!("Best move: none, the game is over"),
+        None => println!("Best move: pass"),
+    }
+
+    // Report the outcome from the mover's side as a signed disc difference
+    let outcome = match solution.score {
+        0 => "Draw".to_string(),
+        s if s > 0 => format!("{} wins by {}", player.name(), s),
+        s => format!("{} wins by {}", player.opponent().name(), -s),
+    };
+    println!("Score: {:+} ({})", solution.score, outcome);
+    println!("Nodes: {} in {:.3}s", solution.nodes, elapsed.as_secs_f64());
+}
@@ -0,0 +1,65 @@
+use reversi::pattern::{self, PatternEval, TuneSettings};
+use reversi::selfplay::{self, Settings};
+
+use clap::ArgMatches;
+use std::fs;
+use std::process;
+
+fn write_file(path: &str, text: &str) {
+    if let Err(err) = fs::write(path, text) {
+        eprintln!("Failed to write {}: {}", path, err);
+        process::exit(1);
+    }
+}
+
+fn read_file(path: &str) -> String {
+    fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("Failed to read {}: {}", path, err);
+        process::exit(1);
+    })
+}
+
+// Play self-play games and write their positions as a CSV dataset
+pub fn selfplay(matches: &ArgMatches) {
+    let settings = Settings {
+        games: *matches.get_one::<usize>("games").unwrap(),
+        depth: *matches.get_one::<u32>("depth").unwrap(),
+        randomness: *matches.get_one::<f64>("random").unwrap(),
+        opening_plies: *matches.get_one::<usize>("opening").unwrap(),
+        seed: *matches.get_one::<u64>("seed").unwrap(),
+    };
+    let samples = selfplay::generate(settings, |played| eprint!("\rPlayed {} games", played));
+    eprintln!();
+
+    let output = matches.get_one::<String>("output").unwrap();
+    write_file(output, &selfplay::to_csv(&samples));
+    eprintln!("Wrote {} positions to {}", samples.len(), output);
+}
+
+// Fit pattern weights to a dataset and write them out
+pub fn tune(matches: &ArgMatches) {
+    let path = matches.get_one::<String>("data").unwrap();
+    let samples = selfplay::parse_csv(&read_file(path)).unwrap_or_else(|err| {
+        eprintln!("Invalid dataset {}: {}", path, err);
+        process::exit(1);
+    });
+    // Continue from earlier weights if given
+    let start = match matches.get_one::<String>("weights") {
+        Some(weights) => super::read_weights(weights),
+        None => PatternEval::new(),
+    };
+    let settings = TuneSettings {
+        epochs: *matches.get_one::<usize>("epochs").unwrap(),
+        learning_rate: *matches.get_one::<f32>("rate").unwrap(),
+        seed: *matches.get_one::<u64>("seed").unwrap(),
+    };
+
+    println!("{} positions, starting error {:.2}", samples.len(), pattern::mean_squared_error(&start, &samples));
+    let eval = pattern::tune(start, &samples, settings, |epoch, error| {
+        println!("Epoch {:>3}: mean squared error {:.2}", epoch, error);
+    });
+
+    let output = matches.get_one::<String>("output").unwrap();
+    write_file(output, &eval.to_string());
+    println!("Wrote weights to {}", output);
+}
@@ -0,0 +1,295 @@
+// Full-screen game: move a cursor over the board and press Enter to play.
+//
+// Keys:
+//   arrows, hjkl     move the cursor
+//   tab, shift-tab   jump to the next or previous legal move
+//   enter, space     play at the cursor
+//   ?                move the cursor to a suggested move
+//   u, r             undo or redo a turn (not in timed games)
+//   q, esc           leave the game
+//
+// In a timed game the clocks are shown next to the score and tick down
+// while the side to move thinks.
+
+use reversi::clock::format_clock;
+use reversi::ui::{flipped_squares, last_play, Cursor, Direction};
+use reversi::{format_move, print_board, Cell, Clock, Game, Goal, History, Player};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::io::{self, Stdout, Write};
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use crate::{hint_move, redo_turn, stop_clock, undo_turn, ChooseMove};
+
+const BOARD: Color = Color::DarkGreen;
+const CURSOR: Color = Color::DarkYellow;
+const LAST_MOVE: Color = Color::DarkRed;
+const LEGAL: Color = Color::Yellow;
+
+// A flipping disc is drawn as its old colour, edge on, then its new colour
+const FLIP_FRAMES: usize = 3;
+const FRAME_TIME: Duration = Duration::from_millis(90);
+
+// How often the clocks are redrawn while waiting for a key
+const TICK: Duration = Duration::from_millis(100);
+
+// The terminal in raw mode on the alternate screen. Dropping it puts the
+// terminal back, even when the game panics.
+struct Screen {
+    out: Stdout,
+}
+
+impl Screen {
+    fn open() -> io::Result<Screen> {
+        let mut out = io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(out, EnterAlternateScreen, Hide)?;
+        Ok(Screen { out })
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(self.out, Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// Discs part way through flipping
+struct Flip<'a> {
+    squares: &'a [(usize, usize)],
+    frame: usize,
+}
+
+// Play on `history` until the game ends or the player leaves, then print
+// the final board on the normal screen
+pub fn run<const N: usize>(
+    history: &mut History<N>,
+    computer: Option<Player>,
+    clock: Option<&mut Clock>,
+    choose_move: &mut ChooseMove<N>,
+) {
+    let result = Screen::open().and_then(|mut screen| game_loop(&mut screen, history, computer, clock, choose_move));
+    if let Err(err) = result {
+        eprintln!("Terminal error: {}", err);
+        process::exit(1);
+    }
+    print_board(&history.game().board());
+}
+
+fn game_loop<const N: usize>(
+    screen: &mut Screen,
+    history: &mut History<N>,
+    computer: Option<Player>,
+    mut clock: Option<&mut Clock>,
+    choose_move: &mut ChooseMove<N>,
+) -> io::Result<()> {
+    let mut cursor = Cursor::<N>::new(N / 2 - 1, N / 2 - 1);
+    cursor.next_legal(history.game());
+    let mut message = String::new();
+
+    loop {
+        let game = history.game().clone();
+        let flagged = clock.as_deref().and_then(Clock::flagged);
+        let over = flagged.is_some() || game.is_over();
+        if over {
+            stop_clock(&mut clock);
+        }
+        if let Some(player) = flagged {
+            message = format!("{} ran out of time: {} wins. Press q to leave.", player.name(), player.opponent().name());
+        } else if game.is_over() {
+            message = match game.winner() {
+                Some(player) => format!("Game over: {} wins. Press q to leave.", player.name()),
+                None => "Game over: draw. Press q to leave.".to_string(),
+            };
+        } else if game.must_pass() {
+            message = format!("{} has no valid move and passes.", game.to_move().name());
+            history.pass();
+            continue;
+        } else {
+            // The side to move's clock runs until it plays
+            if let Some(clock) = clock.as_deref_mut() {
+                clock.start(game.to_move());
+            }
+            if computer == Some(game.to_move()) {
+                draw(screen, history, cursor, clock.as_deref(), "Computer is thinking...", None, false)?;
+                let (row, col) = choose_move(&game, clock.as_deref()).expect("side to move has a legal move");
+                if !stop_clock(&mut clock) {
+                    continue;
+                }
+                history.play(row, col);
+                animate(screen, &game, history, cursor, clock.as_deref())?;
+                message = format!("Computer played {}.", format_move(row, col));
+                continue;
+            }
+        }
+
+        let human = !over;
+        draw(screen, history, cursor, clock.as_deref(), &message, None, human)?;
+        // Keep the running clock ticking on screen
+        if clock.as_deref().and_then(Clock::running).is_some() && !event::poll(TICK)? {
+            continue;
+        }
+        let key = match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => key,
+            _ => continue,
+        };
+        message.clear();
+
+        match key {
+            KeyEvent { code: KeyCode::Char('c'), modifiers, .. } if modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(())
+            }
+            KeyEvent { code: KeyCode::Char('q') | KeyCode::Esc, .. } => return Ok(()),
+            KeyEvent { code: KeyCode::Up | KeyCode::Char('k'), .. } => cursor.step(Direction::Up),
+            KeyEvent { code: KeyCode::Down | KeyCode::Char('j'), .. } => cursor.step(Direction::Down),
+            KeyEvent { code: KeyCode::Left | KeyCode::Char('h'), .. } => cursor.step(Direction::Left),
+            KeyEvent { code: KeyCode::Right | KeyCode::Char('l'), .. } => cursor.step(Direction::Right),
+            KeyEvent { code: KeyCode::Tab, .. } => cursor.next_legal(&game),
+            KeyEvent { code: KeyCode::BackTab, .. } => cursor.prev_legal(&game),
+            KeyEvent { code: KeyCode::Enter | KeyCode::Char(' '), .. } if human => {
+                if let Err(err) = game.check_move(cursor.row, cursor.col) {
+                    message = format!("Invalid move: {}. Try again.", err);
+                } else if stop_clock(&mut clock) {
+                    history.play(cursor.row, cursor.col);
+                    animate(screen, &game, history, cursor, clock.as_deref())?;
+                }
+            }
+            KeyEvent { code: KeyCode::Char('?'), .. } if human => {
+                if let Some((row, col)) = hint_move(&game, choose_move) {
+                    cursor = Cursor::new(row, col);
+                    message = format!("Hint: {}", format_move(row, col));
+                }
+            }
+            KeyEvent { code: KeyCode::Char('u' | 'r'), .. } if clock.is_some() => {
+                message = "Undo and redo are not available in a timed game.".to_string();
+            }
+            KeyEvent { code: KeyCode::Char('u'), .. } if !undo_turn(history, computer) => {
+                message = "Nothing to undo.".to_string();
+            }
+            KeyEvent { code: KeyCode::Char('r'), .. } if !redo_turn(history, computer) => {
+                message = "Nothing to redo.".to_string();
+            }
+            _ => {}
+        }
+    }
+}
+
+// Show the discs flipped by the move from `before` to the current position
+fn animate<const N: usize>(
+    screen: &mut Screen,
+    before: &Game<N>,
+    history: &History<N>,
+    cursor: Cursor<N>,
+    clock: Option<&Clock>,
+) -> io::Result<()> {
+    let squares = flipped_squares(before, history.game());
+    for frame in 0..FLIP_FRAMES {
+        draw(screen, history, cursor, clock, "", Some(Flip { squares: &squares, frame }), false)?;
+        thread::sleep(FRAME_TIME);
+    }
+    Ok(())
+}
+
+// Redraw the whole screen: the board on the left, the score panel (with
+// the clocks in a timed game) on the right and a message line underneath.
+// Legal moves are marked when `show_legal` is set.
+fn draw<const N: usize>(
+    screen: &mut Screen,
+    history: &History<N>,
+    cursor: Cursor<N>,
+    clock: Option<&Clock>,
+    message: &str,
+    flip: Option<Flip>,
+    show_legal: bool,
+) -> io::Result<()> {
+    let out = &mut screen.out;
+    let game = history.game();
+    let last = last_play(history.moves());
+    queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+
+    // Each square is three columns wide with the disc in the middle
+    let header: String = (b'a'..b'a' + N as u8).map(|c| format!(" {} ", c as char)).collect();
+    queue!(out, Print(format!("  {}", header)))?;
+    for row in 0..N {
+        queue!(out, MoveTo(0, row as u16 + 1), Print(format!("{} ", (b'a' + row as u8) as char)))?;
+        for col in 0..N {
+            let background = if (row, col) == (cursor.row, cursor.col) {
+                CURSOR
+            } else if Some((row, col)) == last {
+                LAST_MOVE
+            } else {
+                BOARD
+            };
+            let (symbol, colour) = match game.cell(row, col) {
+                Cell::Empty if show_legal && game.is_legal(row, col) => ('·', LEGAL),
+                Cell::Empty => (' ', BOARD),
+                Cell::Blocked => ('#', Color::DarkGrey),
+                cell => {
+                    let player = cell.player().expect("cell holds a disc");
+                    match &flip {
+                        Some(flip) if flip.squares.contains(&(row, col)) => match flip.frame {
+                            0 => ('●', disc_colour(player.opponent())),
+                            1 => ('|', Color::Grey),
+                            _ => ('●', disc_colour(player)),
+                        },
+                        _ => ('●', disc_colour(player)),
+                    }
+                }
+            };
+            queue!(
+                out,
+                SetBackgroundColor(background),
+                SetForegroundColor(colour),
+                Print(format!(" {} ", symbol)),
+                ResetColor
+            )?;
+        }
+    }
+
+    let (black, white) = game.score();
+    let panel = 3 * N as u16 + 5;
+    let turn = |player: Player| if game.to_move() == player && !game.is_over() { '>' } else { ' ' };
+    let time = |player: Player| match clock {
+        Some(clock) => format!("  {:>6}", format_clock(clock.remaining(player))),
+        None => String::new(),
+    };
+    let lines = [
+        format!("{} Black  {:>3}{}", turn(Player::Black), black, time(Player::Black)),
+        format!("{} White  {:>3}{}", turn(Player::White), white, time(Player::White)),
+        match game.goal() {
+            Goal::FewestDiscs => "  Fewest discs wins".to_string(),
+            Goal::MostDiscs => String::new(),
+        },
+        match last {
+            Some((row, col)) => format!("  Last move: {}", format_move(row, col)),
+            None => String::new(),
+        },
+        String::new(),
+        "  arrows  move".to_string(),
+        "  tab     next legal move".to_string(),
+        "  enter   play".to_string(),
+        "  ?       hint".to_string(),
+        "  u / r   undo / redo".to_string(),
+        "  q       quit".to_string(),
+    ];
+    for (i, line) in lines.iter().enumerate() {
+        queue!(out, MoveTo(panel, i as u16 + 1), Print(line))?;
+    }
+
+    queue!(out, MoveTo(0, N as u16 + 2), Print(message))?;
+    out.flush()
+}
+
+fn disc_colour(player: Player) -> Color {
+    match player {
+        Player::Black => Color::Black,
+        Player::White => Color::White,
+    }
+}
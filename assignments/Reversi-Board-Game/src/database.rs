@@ -0,0 +1,249 @@
+// A database of played games that answers questions about positions: how
+// many games reached a position, how they ended and which moves were
+// played from it.
+//
+// Games are kept as transcripts in standard notation from the usual start.
+// Every position they pass through is indexed by the hash of its canonical
+// copy among the eight symmetries of the board, so a question about the
+// position after f5 also counts games that opened d3, c4 or e6. Database
+// files have one game per line:
+//
+//   f5d6c3d3c4f4f6f3e6e7d7g6f8f7g5h6h4g4h3h5e8g3c5b4e3c6d8c8b3a3a4b5c7b6a6
+//
+// Blank lines and lines starting with '#' are ignored.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::bitboard::{coords, inverse_symmetry, square, transform_square, Bitboard};
+use crate::game::{Game, Player};
+use crate::history::{History, Move, TranscriptError};
+
+// Why games could not be added. Line numbers start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseError {
+    pub line: usize,
+    pub error: TranscriptError,
+}
+
+impl fmt::Display for DatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+impl std::error::Error for DatabaseError {}
+
+// How a set of games ended. Games that stop before the end count towards
+// `unfinished` only.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Outcomes {
+    pub black_wins: u32,
+    pub white_wins: u32,
+    pub draws: u32,
+    pub unfinished: u32,
+}
+
+impl Outcomes {
+    // Every game, finished or not
+    pub fn games(&self) -> u32 {
+        self.finished() + self.unfinished
+    }
+
+    pub fn finished(&self) -> u32 {
+        self.black_wins + self.white_wins + self.draws
+    }
+
+    pub fn wins(&self, player: Player) -> u32 {
+        match player {
+            Player::Black => self.black_wins,
+            Player::White => self.white_wins,
+        }
+    }
+
+    // The share of finished games `player` won, or None without any
+    pub fn win_rate(&self, player: Player) -> Option<f64> {
+        (self.finished() > 0).then(|| self.wins(player) as f64 / self.finished() as f64)
+    }
+
+    // The share of finished games that were drawn, or None without any
+    pub fn draw_rate(&self) -> Option<f64> {
+        (self.finished() > 0).then(|| self.draws as f64 / self.finished() as f64)
+    }
+
+    fn add(&mut self, result: Option<Option<Player>>) {
+        match result {
+            Some(Some(Player::Black)) => self.black_wins += 1,
+            Some(Some(Player::White)) => self.white_wins += 1,
+            Some(None) => self.draws += 1,
+            None => self.unfinished += 1,
+        }
+    }
+}
+
+// A move played from a position, with how the games that played it ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Continuation {
+    pub mv: Move,
+    pub outcomes: Outcomes,
+}
+
+// What the database knows about a position
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PositionStats {
+    // The games that reached the position
+    pub outcomes: Outcomes,
+    // The moves played next, most played first
+    pub continuations: Vec<Continuation>,
+}
+
+// Everything recorded for one canonical position. Moves are squares of the
+// canonical copy.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Entry {
+    outcomes: Outcomes,
+    next: Vec<(Move, Outcomes)>,
+}
+
+// The index key of a position's canonical copy, and the symmetry that maps
+// the position onto it
+fn canonical(board: &Bitboard, player: Player) -> (u64, usize) {
+    let (image, symmetry) = board.canonical();
+    (image.key(player), symmetry)
+}
+
+fn transform_move(mv: Move, symmetry: usize) -> Move {
+    match mv {
+        Move::Play(row, col) => {
+            let (row, col) = coords(transform_square(square(row, col), symmetry));
+            Move::Play(row, col)
+        }
+        Move::Pass => Move::Pass,
+    }
+}
+
+// Stored games and the index of their positions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Database {
+    transcripts: Vec<String>,
+    index: HashMap<u64, Entry>,
+}
+
+impl Database {
+    pub fn new() -> Database {
+        Database::default()
+    }
+
+    // Number of games
+    pub fn len(&self) -> usize {
+        self.transcripts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transcripts.is_empty()
+    }
+
+    // Number of distinct positions, counting symmetric copies once
+    pub fn positions(&self) -> usize {
+        self.index.len()
+    }
+
+    // The stored games in the order they were added
+    pub fn transcripts(&self) -> &[String] {
+        &self.transcripts
+    }
+
+    // Index the positions of a game played from the standard start
+    fn add_game(&mut self, history: &History) {
+        let end = history.game();
+        let result = end.is_over().then(|| end.winner());
+
+        let mut game = history.start().clone();
+        let mut moves = history.moves().iter();
+        loop {
+            let (key, symmetry) = canonical(&Bitboard::from_grid(game.grid()), game.to_move());
+            let entry = self.index.entry(key).or_default();
+            entry.outcomes.add(result);
+            let Some(&mv) = moves.next() else { break };
+            let stored = transform_move(mv, symmetry);
+            match entry.next.iter_mut().find(|(known, _)| *known == stored) {
+                Some((_, outcomes)) => outcomes.add(result),
+                None => {
+                    let mut outcomes = Outcomes::default();
+                    outcomes.add(result);
+                    entry.next.push((stored, outcomes));
+                }
+            }
+            match mv {
+                Move::Play(row, col) => game.play(row, col),
+                Move::Pass => game.pass(),
+            };
+        }
+        self.transcripts.push(history.to_transcript());
+    }
+
+    // Add a game from its transcript in standard notation
+    pub fn add_transcript(&mut self, transcript: &str) -> Result<(), TranscriptError> {
+        let history = History::from_transcript(transcript)?;
+        self.add_game(&history);
+        Ok(())
+    }
+
+    // Add every game in a text in the file format described above. Nothing
+    // is added if any game is invalid.
+    pub fn add_text(&mut self, text: &str) -> Result<usize, DatabaseError> {
+        let mut histories = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let history = History::from_transcript(line).map_err(|error| DatabaseError { line: i + 1, error })?;
+            histories.push(history);
+        }
+        for history in &histories {
+            self.add_game(history);
+        }
+        Ok(histories.len())
+    }
+
+    // Read a database in the file format described above
+    pub fn parse(text: &str) -> Result<Database, DatabaseError> {
+        let mut database = Database::new();
+        database.add_text(text)?;
+        Ok(database)
+    }
+
+    // What is known about a position; all zero if no game reached it
+    pub fn stats(&self, game: &Game) -> PositionStats {
+        let (key, symmetry) = canonical(&Bitboard::from_grid(game.grid()), game.to_move());
+        let Some(entry) = self.index.get(&key) else {
+            return PositionStats::default();
+        };
+        let inverse = inverse_symmetry(symmetry);
+        let mut continuations: Vec<Continuation> = entry
+            .next
+            .iter()
+            .map(|&(mv, outcomes)| Continuation { mv: transform_move(mv, inverse), outcomes })
+            .collect();
+        // Most played first, then in board order so the listing is stable
+        continuations.sort_by_key(|continuation| {
+            let order = match continuation.mv {
+                Move::Play(row, col) => square(row, col),
+                Move::Pass => 0,
+            };
+            (std::cmp::Reverse(continuation.outcomes.games()), order)
+        });
+        PositionStats { outcomes: entry.outcomes, continuations }
+    }
+}
+
+// The database in its file format
+impl fmt::Display for Database {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for transcript in &self.transcripts {
+            writeln!(f, "{}", transcript)?;
+        }
+        Ok(())
+    }
+}
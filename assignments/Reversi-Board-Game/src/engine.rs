@@ -0,0 +1,558 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::bitboard::{coords, square, squares, Bitboard};
+use crate::book::Book;
+use crate::clock;
+use crate::game::{Game, Player};
+use crate::history::Move;
+use crate::pattern::PatternEval;
+use crate::rules::Goal;
+
+// Scores at or beyond this magnitude are decided games
+pub const WIN_SCORE: i32 = 1_000_000;
+
+const INFINITY: i32 = 2 * WIN_SCORE;
+const MAX_DEPTH: u32 = 64;
+const CORNERS: u64 = 0x8100_0000_0000_0081;
+
+// How often (in nodes) a timed search looks at the clock
+const CLOCK_CHECK_INTERVAL: u64 = 1024;
+
+// A static evaluation of a position. Evaluators are shared between search
+// threads, so they must be `Sync`.
+pub trait Evaluate: Sync {
+    // Score the position from `player`'s point of view; higher is better.
+    // Results must stay well below `WIN_SCORE`.
+    fn evaluate(&self, board: &Bitboard, player: Player) -> i32;
+
+    // Short label for reports, e.g. the "engine" of "engine:4"
+    fn name(&self) -> &'static str {
+        "engine"
+    }
+}
+
+// Any closure with the right signature can be used as an evaluation
+impl<F> Evaluate for F
+where
+    F: Fn(&Bitboard, Player) -> i32 + Sync,
+{
+    fn evaluate(&self, board: &Bitboard, player: Player) -> i32 {
+        self(board, player)
+    }
+}
+
+// Weighted sum of mobility, corner and disc differences
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Heuristic {
+    pub mobility: i32,
+    pub corners: i32,
+    pub discs: i32,
+}
+
+impl Default for Heuristic {
+    fn default() -> Self {
+        Heuristic { mobility: 10, corners: 100, discs: 1 }
+    }
+}
+
+impl Heuristic {
+    // The default weights for a goal: in anti-reversi discs count against
+    // their owner
+    pub fn for_goal(goal: Goal) -> Heuristic {
+        let heuristic = Heuristic::default();
+        match goal {
+            Goal::MostDiscs => heuristic,
+            Goal::FewestDiscs => Heuristic { discs: -heuristic.discs, ..heuristic },
+        }
+    }
+}
+
+impl Evaluate for Heuristic {
+    fn evaluate(&self, board: &Bitboard, player: Player) -> i32 {
+        let own = board.discs(player);
+        let opp = board.discs(player.opponent());
+        let mobility = board.legal_moves(player).count_ones() as i32
+            - board.legal_moves(player.opponent()).count_ones() as i32;
+        let corners = (own & CORNERS).count_ones() as i32 - (opp & CORNERS).count_ones() as i32;
+        let discs = own.count_ones() as i32 - opp.count_ones() as i32;
+        self.mobility * mobility + self.corners * corners + self.discs * discs
+    }
+}
+
+// An evaluation picked at run time, such as pattern weights read from a
+// file instead of the default heuristic
+#[derive(Debug, Clone, PartialEq)]
+pub enum Evaluation {
+    Heuristic(Heuristic),
+    Pattern(PatternEval),
+}
+
+impl Default for Evaluation {
+    fn default() -> Self {
+        Evaluation::Heuristic(Heuristic::default())
+    }
+}
+
+impl Evaluate for Evaluation {
+    fn evaluate(&self, board: &Bitboard, player: Player) -> i32 {
+        match self {
+            Evaluation::Heuristic(heuristic) => heuristic.evaluate(board, player),
+            Evaluation::Pattern(pattern) => pattern.evaluate(board, player),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Evaluation::Heuristic(heuristic) => heuristic.name(),
+            Evaluation::Pattern(pattern) => pattern.name(),
+        }
+    }
+}
+
+// Score of a finished game from `player`'s point of view: a win always
+// beats any heuristic score, and bigger wins beat smaller ones
+pub fn final_score(board: &Bitboard, player: Player) -> i32 {
+    final_score_for_goal(board, player, Goal::MostDiscs)
+}
+
+// Like `final_score`, for a game won by `goal`. Under `Goal::FewestDiscs`
+// the disc difference counts the other way.
+pub fn final_score_for_goal(board: &Bitboard, player: Player, goal: Goal) -> i32 {
+    let diff = board.discs(player).count_ones() as i32
+        - board.discs(player.opponent()).count_ones() as i32;
+    let diff = match goal {
+        Goal::MostDiscs => diff,
+        Goal::FewestDiscs => -diff,
+    };
+    match diff {
+        0 => 0,
+        d if d > 0 => WIN_SCORE + d,
+        d => -WIN_SCORE + d,
+    }
+}
+
+// How long the engine may think
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    // Search exactly this many plies
+    Depth(u32),
+    // Deepen until the time is used up; the first ply is always completed
+    Time(Duration),
+    // Like `Time`, with a budget worked out from the mover's game clock and
+    // the number of moves left (see `clock::budget`)
+    Clock { remaining: Duration, increment: Duration },
+}
+
+// The outcome of a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchResult {
+    // Square index of the best move, or None if the side to move must pass
+    pub best_move: Option<usize>,
+    // Score of the best move from the mover's point of view
+    pub score: i32,
+    // Deepest fully completed iteration
+    pub depth: u32,
+    // Positions visited over all iterations
+    pub nodes: u64,
+}
+
+// One root move with its exact score and the line of play the search
+// expects after it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variation {
+    // Score from the mover's point of view
+    pub score: i32,
+    // The principal variation, starting with the root move. Passes are
+    // included so the sides alternate.
+    pub moves: Vec<Move>,
+}
+
+// The assessment of every legal move after one iteration of `analyze`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    pub depth: u32,
+    // Positions visited over all iterations so far
+    pub nodes: u64,
+    // One per legal move, best first
+    pub variations: Vec<Variation>,
+}
+
+// Negamax search with alpha-beta pruning and iterative deepening
+#[derive(Debug, Clone)]
+pub struct Engine<E = Heuristic> {
+    evaluator: E,
+    limit: Limit,
+    // What wins the games searched; decides the score of finished games
+    goal: Goal,
+    // Workers for parallel search; None searches on the calling thread
+    pool: Option<Arc<ThreadPool>>,
+    // Opening book consulted before searching, with the random source for
+    // its weighted picks
+    book: Option<(Book, StdRng)>,
+}
+
+impl Default for Engine<Heuristic> {
+    fn default() -> Self {
+        Engine::new(Heuristic::default(), Limit::Depth(6))
+    }
+}
+
+impl<E: Evaluate> Engine<E> {
+    pub fn new(evaluator: E, limit: Limit) -> Engine<E> {
+        Engine { evaluator, limit, goal: Goal::MostDiscs, pool: None, book: None }
+    }
+
+    pub fn evaluator(&self) -> &E {
+        &self.evaluator
+    }
+
+    pub fn limit(&self) -> Limit {
+        self.limit
+    }
+
+    pub fn set_limit(&mut self, limit: Limit) {
+        self.limit = limit;
+    }
+
+    // The goal searched for; `Goal::MostDiscs` unless `set_goal` was called
+    pub fn goal(&self) -> Goal {
+        self.goal
+    }
+
+    // Search for a variant's goal. Only finished games are scored by it, so
+    // the evaluator should be meant for the same goal, as
+    // `Heuristic::for_goal` is. The book is not used for other goals.
+    pub fn set_goal(&mut self, goal: Goal) {
+        self.goal = goal;
+    }
+
+    // Number of threads searching; 1 unless `set_threads` was called
+    pub fn threads(&self) -> usize {
+        self.pool.as_ref().map_or(1, |pool| pool.current_num_threads())
+    }
+
+    // Search with this many threads. The root moves after the first are
+    // split between them, which gives the same best move and score as a
+    // single thread at a fixed depth. 0 means one per CPU.
+    pub fn set_threads(&mut self, threads: usize) {
+        let threads = if threads == 0 { rayon::current_num_threads() } else { threads };
+        self.pool = (threads > 1).then(|| {
+            let pool = ThreadPoolBuilder::new().num_threads(threads).build();
+            Arc::new(pool.expect("failed to start search threads"))
+        });
+    }
+
+    // Play book moves while the position is in `book`. `seed` makes the
+    // choice between weighted moves reproducible.
+    pub fn set_book(&mut self, book: Book, seed: u64) {
+        self.book = Some((book, StdRng::seed_from_u64(seed)));
+    }
+
+    pub fn book(&self) -> Option<&Book> {
+        self.book.as_ref().map(|(book, _)| book)
+    }
+
+    // Pick a move for the side to move in a game, or None to pass. A book
+    // move is played without searching when there is one.
+    pub fn choose_move(&mut self, game: &Game) -> Option<(usize, usize)> {
+        let board = Bitboard::from_grid(game.grid());
+        if let Some((book, rng)) = self.book.as_mut().filter(|_| self.goal == Goal::MostDiscs) {
+            if let Some(sq) = book.choose(&board, game.to_move(), rng) {
+                return Some(crate::bitboard::coords(sq));
+            }
+        }
+        self.search(&board, game.to_move())
+            .best_move
+            .map(crate::bitboard::coords)
+    }
+
+    // Search the position for `player` within the configured limit
+    pub fn search(&mut self, board: &Bitboard, player: Player) -> SearchResult {
+        let moves = board.legal_moves(player);
+        let Some(first) = squares(moves).next() else {
+            return SearchResult { best_move: None, score: 0, depth: 0, nodes: 0 };
+        };
+
+        let empties = board.empty().count_ones();
+        let (max_depth, deadline) = match self.limit {
+            Limit::Depth(depth) => (depth.max(1), None),
+            Limit::Time(budget) => (MAX_DEPTH, Some(Instant::now() + budget)),
+            Limit::Clock { remaining, increment } => {
+                (MAX_DEPTH, Some(Instant::now() + clock::budget(remaining, increment, empties)))
+            }
+        };
+        let stop = AtomicBool::new(false);
+        let mut nodes = 0;
+
+        let mut result = SearchResult { best_move: Some(first), score: 0, depth: 0, nodes: 0 };
+        for depth in 1..=max_depth {
+            // Never abort the first iteration so there is always a real move
+            let deadline = if depth == 1 { None } else { deadline };
+            let (best, score, used) = self.search_root(board, player, depth, result.best_move, deadline, &stop);
+            nodes += used;
+            if stop.load(Ordering::Relaxed) {
+                break;
+            }
+            result = SearchResult { best_move: Some(best), score, depth, nodes };
+
+            // Deeper iterations cannot change a result that already reaches
+            // the end of the game
+            if depth >= empties || score.abs() >= WIN_SCORE {
+                break;
+            }
+        }
+        result.nodes = nodes;
+        result
+    }
+
+    // Score every legal move exactly, with its principal variation, by
+    // iterative deepening within the configured limit. `progress` is called
+    // after each completed depth. Unlike `search`, no move is cut off early,
+    // so this is slower. Returns the last completed iteration, with no
+    // variations if the side to move must pass.
+    pub fn analyze(&self, board: &Bitboard, player: Player, mut progress: impl FnMut(&Analysis)) -> Analysis {
+        let empties = board.empty().count_ones();
+        let (max_depth, deadline) = match self.limit {
+            Limit::Depth(depth) => (depth.max(1), None),
+            Limit::Time(budget) => (MAX_DEPTH, Some(Instant::now() + budget)),
+            Limit::Clock { remaining, increment } => {
+                (MAX_DEPTH, Some(Instant::now() + clock::budget(remaining, increment, empties)))
+            }
+        };
+        let stop = AtomicBool::new(false);
+        let mut analysis = Analysis { depth: 0, nodes: 0, variations: Vec::new() };
+        // Searched best first, so the deepest lines come from the previous
+        // iteration's ranking
+        let mut moves: Vec<usize> = ordered_moves(board.legal_moves(player), None);
+
+        for depth in 1..=max_depth {
+            if moves.is_empty() {
+                break;
+            }
+            // Never abort the first iteration so every move gets a score
+            let deadline = if depth == 1 { None } else { deadline };
+            let search_move = |sq: usize| {
+                let mut searcher = Searcher { evaluator: &self.evaluator, nodes: 0, deadline, stop: &stop, goal: self.goal };
+                let mut child = *board;
+                child.apply(player, sq);
+                let mut line = Vec::new();
+                let score = -searcher.negamax_pv(&child, player.opponent(), depth - 1, -INFINITY, INFINITY, &mut line);
+                let (row, col) = coords(sq);
+                line.insert(0, Move::Play(row, col));
+                (Variation { score, moves: line }, searcher.nodes)
+            };
+            let results: Vec<(Variation, u64)> = match &self.pool {
+                Some(pool) => pool.install(|| moves.par_iter().map(|&sq| search_move(sq)).collect()),
+                None => moves.iter().map(|&sq| search_move(sq)).collect(),
+            };
+            analysis.nodes += results.iter().map(|(_, nodes)| nodes).sum::<u64>();
+            if stop.load(Ordering::Relaxed) {
+                break;
+            }
+
+            let mut variations: Vec<Variation> = results.into_iter().map(|(variation, _)| variation).collect();
+            // Stable, so equal scores keep the search order
+            variations.sort_by_key(|variation| std::cmp::Reverse(variation.score));
+            moves = variations
+                .iter()
+                .map(|variation| match variation.moves[0] {
+                    Move::Play(row, col) => square(row, col),
+                    Move::Pass => unreachable!("root moves are never passes"),
+                })
+                .collect();
+            analysis.depth = depth;
+            analysis.variations = variations;
+            progress(&analysis);
+
+            // Deeper iterations cannot change scores that reach the end of
+            // the game
+            if depth >= empties || analysis.variations.iter().all(|variation| variation.score.abs() >= WIN_SCORE) {
+                break;
+            }
+        }
+        analysis
+    }
+
+    // Search every root move to `depth`. Returns the best move, its score
+    // and the number of nodes visited.
+    fn search_root(
+        &self,
+        board: &Bitboard,
+        player: Player,
+        depth: u32,
+        hint: Option<usize>,
+        deadline: Option<Instant>,
+        stop: &AtomicBool,
+    ) -> (usize, i32, u64) {
+        let moves = ordered_moves(board.legal_moves(player), hint);
+        let mut searcher = Searcher { evaluator: &self.evaluator, nodes: 0, deadline, stop, goal: self.goal };
+        let mut alpha = -INFINITY;
+        let mut best = moves[0];
+
+        let Some(pool) = &self.pool else {
+            for sq in moves {
+                let mut child = *board;
+                child.apply(player, sq);
+                let score = -searcher.negamax(&child, player.opponent(), depth - 1, -INFINITY, -alpha);
+                if searcher.aborted() {
+                    break;
+                }
+                if score > alpha {
+                    alpha = score;
+                    best = sq;
+                }
+            }
+            return (best, alpha, searcher.nodes);
+        };
+
+        // Young brothers wait: the first move, usually the best, is searched
+        // alone to get a bound, then its brothers are searched in parallel
+        // against that bound. Moves that beat it get exact scores, so taking
+        // the first highest in order picks what a single thread would.
+        let mut child = *board;
+        child.apply(player, best);
+        alpha = -searcher.negamax(&child, player.opponent(), depth - 1, -INFINITY, INFINITY);
+        let bound = alpha;
+        let brothers: Vec<(i32, u64)> = pool.install(|| {
+            moves[1..]
+                .par_iter()
+                .map(|&sq| {
+                    let mut searcher = Searcher { evaluator: &self.evaluator, nodes: 0, deadline, stop, goal: self.goal };
+                    let mut child = *board;
+                    child.apply(player, sq);
+                    let score = -searcher.negamax(&child, player.opponent(), depth - 1, -INFINITY, -bound);
+                    (score, searcher.nodes)
+                })
+                .collect()
+        });
+
+        let mut nodes = searcher.nodes;
+        for (&sq, (score, used)) in moves[1..].iter().zip(brothers) {
+            nodes += used;
+            if score > alpha {
+                alpha = score;
+                best = sq;
+            }
+        }
+        (best, alpha, nodes)
+    }
+}
+
+// The state of one search thread
+struct Searcher<'a, E> {
+    evaluator: &'a E,
+    nodes: u64,
+    deadline: Option<Instant>,
+    // Set by the first thread to run out of time, stopping all of them
+    stop: &'a AtomicBool,
+    goal: Goal,
+}
+
+impl<E: Evaluate> Searcher<'_, E> {
+    fn aborted(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    fn negamax(&mut self, board: &Bitboard, player: Player, depth: u32, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if let Some(deadline) = self.deadline {
+            if self.nodes.is_multiple_of(CLOCK_CHECK_INTERVAL) && Instant::now() >= deadline {
+                self.stop.store(true, Ordering::Relaxed);
+            }
+        }
+        if self.aborted() {
+            return 0;
+        }
+
+        let moves = board.legal_moves(player);
+        if moves == 0 {
+            if board.legal_moves(player.opponent()) == 0 {
+                return final_score_for_goal(board, player, self.goal);
+            }
+            // Passing does not use up a ply; two passes in a row end the game
+            return -self.negamax(board, player.opponent(), depth, -beta, -alpha);
+        }
+        if depth == 0 {
+            return self.evaluator.evaluate(board, player);
+        }
+
+        for sq in ordered_moves(moves, None) {
+            let mut child = *board;
+            child.apply(player, sq);
+            let score = -self.negamax(&child, player.opponent(), depth - 1, -beta, -alpha);
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+
+    // Like `negamax`, also filling `line` with the principal variation from
+    // this position. Only scores inside the window come with a full line.
+    fn negamax_pv(&mut self, board: &Bitboard, player: Player, depth: u32, mut alpha: i32, beta: i32, line: &mut Vec<Move>) -> i32 {
+        line.clear();
+        self.nodes += 1;
+        if let Some(deadline) = self.deadline {
+            if self.nodes.is_multiple_of(CLOCK_CHECK_INTERVAL) && Instant::now() >= deadline {
+                self.stop.store(true, Ordering::Relaxed);
+            }
+        }
+        if self.aborted() {
+            return 0;
+        }
+
+        let moves = board.legal_moves(player);
+        if moves == 0 {
+            if board.legal_moves(player.opponent()) == 0 {
+                return final_score_for_goal(board, player, self.goal);
+            }
+            let mut rest = Vec::new();
+            let score = -self.negamax_pv(board, player.opponent(), depth, -beta, -alpha, &mut rest);
+            line.push(Move::Pass);
+            line.extend(rest);
+            return score;
+        }
+        if depth == 0 {
+            return self.evaluator.evaluate(board, player);
+        }
+
+        let mut rest = Vec::new();
+        for sq in ordered_moves(moves, None) {
+            let mut child = *board;
+            child.apply(player, sq);
+            let score = -self.negamax_pv(&child, player.opponent(), depth - 1, -beta, -alpha, &mut rest);
+            if score > alpha {
+                let (row, col) = coords(sq);
+                line.clear();
+                line.push(Move::Play(row, col));
+                line.extend_from_slice(&rest);
+            }
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+}
+
+// Search order: the hinted move, then corners, then everything else
+fn ordered_moves(moves: u64, hint: Option<usize>) -> Vec<usize> {
+    let mut ordered = Vec::with_capacity(moves.count_ones() as usize);
+    let mut rest = moves;
+    if let Some(hint) = hint.filter(|&sq| moves & (1 << sq) != 0) {
+        ordered.push(hint);
+        rest &= !(1 << hint);
+    }
+    ordered.extend(squares(rest & CORNERS));
+    ordered.extend(squares(rest & !CORNERS));
+    ordered
+}
@@ -0,0 +1,482 @@
+use std::fmt;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::rules::Goal;
+use crate::{format_move, zobrist, Board, SIZE};
+
+// Direction vectors for N, S, W, E, NW, NE, SW, SE
+pub const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, 0), (1, 0), (0, -1), (0, 1),
+    (-1, -1), (-1, 1), (1, -1), (1, 1),
+];
+
+// One of the two sides of the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Player {
+    Black,
+    White,
+}
+
+impl Player {
+    // The other side
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::Black => Player::White,
+            Player::White => Player::Black,
+        }
+    }
+
+    // "Black" or "White"
+    pub fn name(self) -> &'static str {
+        match self {
+            Player::Black => "Black",
+            Player::White => "White",
+        }
+    }
+
+    // The character used for this player's discs on a `Board`
+    pub fn to_char(self) -> char {
+        match self {
+            Player::Black => 'B',
+            Player::White => 'W',
+        }
+    }
+
+    // Parse 'B' or 'W'; anything else is not a player
+    pub fn from_char(c: char) -> Option<Player> {
+        match c {
+            'B' => Some(Player::Black),
+            'W' => Some(Player::White),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+// The content of a single square
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Cell {
+    #[default]
+    Empty,
+    Black,
+    White,
+    // A square no disc may occupy, in variants with obstacles
+    Blocked,
+}
+
+impl Cell {
+    // The player owning the disc on this square, if any
+    pub fn player(self) -> Option<Player> {
+        match self {
+            Cell::Empty | Cell::Blocked => None,
+            Cell::Black => Some(Player::Black),
+            Cell::White => Some(Player::White),
+        }
+    }
+
+    // The character used for this cell on a `Board`
+    pub fn to_char(self) -> char {
+        match self {
+            Cell::Empty => '.',
+            Cell::Black => 'B',
+            Cell::White => 'W',
+            Cell::Blocked => '#',
+        }
+    }
+
+    // Parse '.', 'B', 'W' or '#'
+    pub fn from_char(c: char) -> Option<Cell> {
+        match c {
+            '.' => Some(Cell::Empty),
+            '#' => Some(Cell::Blocked),
+            _ => Player::from_char(c).map(Cell::from),
+        }
+    }
+}
+
+impl From<Player> for Cell {
+    fn from(player: Player) -> Cell {
+        match player {
+            Player::Black => Cell::Black,
+            Player::White => Cell::White,
+        }
+    }
+}
+
+// A typed board
+pub type Grid<const N: usize = SIZE> = [[Cell; N]; N];
+
+// Convert a character board into a typed grid. '#' is a blocked square;
+// characters other than 'B', 'W' and '#' are treated as empty squares.
+pub fn grid_from_board<const N: usize>(board: &Board<N>) -> Grid<N> {
+    let mut grid = [[Cell::Empty; N]; N];
+    for (r, row) in board.iter().enumerate() {
+        for (c, &cell) in row.iter().enumerate() {
+            grid[r][c] = Cell::from_char(cell).unwrap_or(Cell::Empty);
+        }
+    }
+    grid
+}
+
+// Convert a typed grid back into a character board
+pub fn board_from_grid<const N: usize>(grid: &Grid<N>) -> Board<N> {
+    let mut board = [['.'; N]; N];
+    for (r, row) in grid.iter().enumerate() {
+        for (c, &cell) in row.iter().enumerate() {
+            board[r][c] = cell.to_char();
+        }
+    }
+    board
+}
+
+// Count the number of discs the player would flip in one direction
+fn flips_in_direction<const N: usize>(grid: &Grid<N>, player: Player, row: usize, col: usize, (dr, dc): (isize, isize)) -> usize {
+    let own = Cell::from(player);
+    let opponent = Cell::from(player.opponent());
+    let mut r = row as isize + dr;
+    let mut c = col as isize + dc;
+    let mut count = 0;
+
+    while r >= 0 && r < N as isize && c >= 0 && c < N as isize {
+        let cur = grid[r as usize][c as usize];
+        if cur == opponent {
+            count += 1;
+        } else if cur == own {
+            return count;
+        } else {
+            return 0;
+        }
+        r += dr;
+        c += dc;
+    }
+    0
+}
+
+// Why a move was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoveError {
+    // The input is not a row letter followed by a column letter
+    BadInput { text: String },
+    // The player is not 'B' or 'W'
+    BadPlayer { found: char },
+    // The square is outside the board
+    OffBoard { row: usize, col: usize },
+    // The square already holds a disc
+    Occupied { row: usize, col: usize },
+    // The square is blocked by the rules
+    Blocked { row: usize, col: usize },
+    // A disc there would not flip anything
+    NoFlips { row: usize, col: usize },
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::BadInput { text } => {
+                write!(f, "'{}' is not a move: enter a row letter then a column letter, like cd", text)
+            }
+            MoveError::BadPlayer { found } => write!(f, "'{}' is not a player, expected 'B' or 'W'", found),
+            MoveError::OffBoard { row, col } => write!(f, "{} is off the board", format_move(*row, *col)),
+            MoveError::Occupied { row, col } => write!(f, "{} is already taken", format_move(*row, *col)),
+            MoveError::Blocked { row, col } => write!(f, "{} is blocked", format_move(*row, *col)),
+            MoveError::NoFlips { row, col } => {
+                write!(f, "{} does not flip any discs", format_move(*row, *col))
+            }
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+// Check if the player may place a disc at (row, col), and if not, why
+pub fn check_move<const N: usize>(grid: &Grid<N>, player: Player, row: usize, col: usize) -> Result<(), MoveError> {
+    if row >= N || col >= N {
+        return Err(MoveError::OffBoard { row, col });
+    }
+    match grid[row][col] {
+        Cell::Empty => {}
+        Cell::Blocked => return Err(MoveError::Blocked { row, col }),
+        _ => return Err(MoveError::Occupied { row, col }),
+    }
+    if !DIRECTIONS.iter().any(|&dir| flips_in_direction(grid, player, row, col, dir) > 0) {
+        return Err(MoveError::NoFlips { row, col });
+    }
+    Ok(())
+}
+
+// Check if the player may place a disc at (row, col)
+pub fn is_legal<const N: usize>(grid: &Grid<N>, player: Player, row: usize, col: usize) -> bool {
+    check_move(grid, player, row, col).is_ok()
+}
+
+// Place a disc at (row, col) and flip the captured discs. The move is not
+// checked for legality; a disc is placed even if nothing is flipped.
+pub fn place<const N: usize>(grid: &mut Grid<N>, player: Player, row: usize, col: usize) {
+    place_with(grid, player, row, col, |_, _, _| {});
+}
+
+// Like `place`, calling `changed` with the row, column and old cell of every
+// square that changes, so callers can keep derived data such as hashes up
+// to date
+pub fn place_with<const N: usize>(
+    grid: &mut Grid<N>,
+    player: Player,
+    row: usize,
+    col: usize,
+    mut changed: impl FnMut(usize, usize, Cell),
+) {
+    let own = Cell::from(player);
+    if grid[row][col] != own {
+        changed(row, col, grid[row][col]);
+    }
+    grid[row][col] = own;
+
+    for &(dr, dc) in &DIRECTIONS {
+        let count = flips_in_direction(grid, player, row, col, (dr, dc));
+        for step in 1..=count as isize {
+            let r = (row as isize + dr * step) as usize;
+            let c = (col as isize + dc * step) as usize;
+            changed(r, c, grid[r][c]);
+            grid[r][c] = own;
+        }
+    }
+}
+
+// Number of discs the player would flip by playing at (row, col); 0 if the
+// move is illegal
+pub fn flip_count<const N: usize>(grid: &Grid<N>, player: Player, row: usize, col: usize) -> usize {
+    if row >= N || col >= N || grid[row][col] != Cell::Empty {
+        return 0;
+    }
+    DIRECTIONS
+        .iter()
+        .map(|&dir| flips_in_direction(grid, player, row, col, dir))
+        .sum()
+}
+
+// A legal move together with the number of discs it flips
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LegalMove {
+    pub row: usize,
+    pub col: usize,
+    pub flips: usize,
+}
+
+// All legal moves for the player with their flip counts, in row-major order
+pub fn list_moves<const N: usize>(grid: &Grid<N>, player: Player) -> Vec<LegalMove> {
+    let mut moves = Vec::new();
+    for row in 0..N {
+        for col in 0..N {
+            let flips = flip_count(grid, player, row, col);
+            if flips > 0 {
+                moves.push(LegalMove { row, col, flips });
+            }
+        }
+    }
+    moves
+}
+
+// All legal moves for the player, in row-major order
+pub fn legal_moves<const N: usize>(grid: &Grid<N>, player: Player) -> Vec<(usize, usize)> {
+    let mut moves = Vec::new();
+    for row in 0..N {
+        for col in 0..N {
+            if is_legal(grid, player, row, col) {
+                moves.push((row, col));
+            }
+        }
+    }
+    moves
+}
+
+// Check if the player has at least one legal move
+pub fn has_legal_move<const N: usize>(grid: &Grid<N>, player: Player) -> bool {
+    (0..N).any(|row| (0..N).any(|col| is_legal(grid, player, row, col)))
+}
+
+// Count the number of black and white discs
+pub fn count<const N: usize>(grid: &Grid<N>) -> (usize, usize) {
+    let cells = grid.iter().flatten();
+    let black = cells.clone().filter(|&&cell| cell == Cell::Black).count();
+    let white = cells.filter(|&&cell| cell == Cell::White).count();
+    (black, white)
+}
+
+// A game in progress: the board plus the side to move, and what wins
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Game<const N: usize = SIZE> {
+    grid: Grid<N>,
+    to_move: Player,
+    goal: Goal,
+    // Zobrist hash of the position, kept up to date move by move
+    hash: u64,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::new()
+    }
+}
+
+impl Game {
+    // The standard starting position with Black to move
+    pub fn new() -> Game {
+        Game::new_sized()
+    }
+}
+
+impl<const N: usize> Game<N> {
+    // The starting position on an N x N board with Black to move
+    pub fn new_sized() -> Game<N> {
+        Game::from_board(&crate::create_initial_board_sized(), Player::Black)
+    }
+
+    // Start from an arbitrary character board
+    pub fn from_board(board: &Board<N>, to_move: Player) -> Game<N> {
+        Game::from_grid(grid_from_board(board), to_move)
+    }
+
+    // Start from an arbitrary typed grid
+    pub fn from_grid(grid: Grid<N>, to_move: Player) -> Game<N> {
+        let hash = zobrist::hash_grid(&grid, to_move);
+        Game { grid, to_move, goal: Goal::MostDiscs, hash }
+    }
+
+    // The same position played to a different goal, e.g. anti-reversi
+    pub fn with_goal(mut self, goal: Goal) -> Game<N> {
+        self.goal = goal;
+        self
+    }
+
+    pub fn goal(&self) -> Goal {
+        self.goal
+    }
+
+    pub fn grid(&self) -> &Grid<N> {
+        &self.grid
+    }
+
+    // The position as a character board, e.g. for `print_board`
+    pub fn board(&self) -> Board<N> {
+        board_from_grid(&self.grid)
+    }
+
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> Cell {
+        self.grid[row][col]
+    }
+
+    // Check if the side to move may play at (row, col)
+    pub fn is_legal(&self, row: usize, col: usize) -> bool {
+        is_legal(&self.grid, self.to_move, row, col)
+    }
+
+    // Like `is_legal`, saying why the move is not
+    pub fn check_move(&self, row: usize, col: usize) -> Result<(), MoveError> {
+        check_move(&self.grid, self.to_move, row, col)
+    }
+
+    // All legal moves for the side to move
+    pub fn legal_moves(&self) -> Vec<(usize, usize)> {
+        legal_moves(&self.grid, self.to_move)
+    }
+
+    // All legal moves for the side to move with the discs each would flip
+    pub fn list_moves(&self) -> Vec<LegalMove> {
+        list_moves(&self.grid, self.to_move)
+    }
+
+    pub fn has_legal_move(&self) -> bool {
+        has_legal_move(&self.grid, self.to_move)
+    }
+
+    // The side to move has no legal move but the game is not over
+    pub fn must_pass(&self) -> bool {
+        !self.has_legal_move() && has_legal_move(&self.grid, self.to_move.opponent())
+    }
+
+    // Neither side can move
+    pub fn is_over(&self) -> bool {
+        !self.has_legal_move() && !has_legal_move(&self.grid, self.to_move.opponent())
+    }
+
+    // Play a move for the side to move and hand the turn over. Returns false
+    // and leaves the game untouched if the move is illegal.
+    pub fn play(&mut self, row: usize, col: usize) -> bool {
+        self.try_play(row, col).is_ok()
+    }
+
+    // Like `play`, saying why an illegal move was refused
+    pub fn try_play(&mut self, row: usize, col: usize) -> Result<(), MoveError> {
+        self.check_move(row, col)?;
+        let own = Cell::from(self.to_move);
+        let hash = &mut self.hash;
+        place_with(&mut self.grid, self.to_move, row, col, |r, c, old| {
+            let index = r * N + c;
+            *hash ^= zobrist::cell_key(old, index) ^ zobrist::cell_key(own, index);
+        });
+        self.hash ^= zobrist::SIDE_KEY;
+        self.to_move = self.to_move.opponent();
+        Ok(())
+    }
+
+    // Pass the turn. Only allowed when the side to move has no legal move
+    // and the game is not over.
+    pub fn pass(&mut self) -> bool {
+        if !self.must_pass() {
+            return false;
+        }
+        self.hash ^= zobrist::SIDE_KEY;
+        self.to_move = self.to_move.opponent();
+        true
+    }
+
+    // Play up to `plies` random legal moves, passing when forced. Stops
+    // early if the game ends.
+    pub fn play_random(&mut self, plies: usize, rng: &mut impl Rng) {
+        for _ in 0..plies {
+            if self.must_pass() {
+                self.pass();
+            }
+            match self.legal_moves().choose(rng) {
+                Some(&(row, col)) => {
+                    self.play(row, col);
+                }
+                None => break,
+            }
+        }
+    }
+
+    // Zobrist hash of the board and side to move. Equal positions always
+    // have equal hashes; different ones almost never do.
+    pub fn key(&self) -> u64 {
+        self.hash
+    }
+
+    // A hash shared by all eight rotations and reflections of the position
+    pub fn canonical_key(&self) -> u64 {
+        zobrist::canonical_hash(&self.grid, self.to_move)
+    }
+
+    // Number of black and white discs
+    pub fn score(&self) -> (usize, usize) {
+        count(&self.grid)
+    }
+
+    // The winner under the game's goal (normally the player with more
+    // discs), or None for a draw
+    pub fn winner(&self) -> Option<Player> {
+        let (black, white) = self.score();
+        self.goal.winner(black, white)
+    }
+}
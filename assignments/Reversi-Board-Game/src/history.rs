@@ -0,0 +1,242 @@
+use std::fmt;
+
+use crate::game::{Game, MoveError};
+use crate::SIZE;
+
+// A single turn: a disc placed at (row, col), or a pass
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Move {
+    Play(usize, usize),
+    Pass,
+}
+
+// Name a square in standard transcript notation: column letter then row
+// number, so (4, 5) is "f5" and (9, 0) is "a10"
+pub fn square_name(row: usize, col: usize) -> String {
+    format!("{}{}", (b'a' + col as u8) as char, row + 1)
+}
+
+// Parse a square in standard transcript notation (like "f5"), in either case
+pub fn parse_square_name(name: &str) -> Option<(usize, usize)> {
+    parse_square_name_sized(name, SIZE)
+}
+
+// Parse a square name on a board with `size` rows and columns
+pub fn parse_square_name_sized(name: &str, size: usize) -> Option<(usize, usize)> {
+    let mut chars = name.chars();
+    let col = chars.next()?.to_ascii_lowercase();
+    let digits = chars.as_str();
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let row: usize = digits.parse().ok()?;
+    let last = (b'a' + size as u8 - 1) as char;
+    if !('a'..=last).contains(&col) || !(1..=size).contains(&row) {
+        return None;
+    }
+    Some((row - 1, col as usize - 'a' as usize))
+}
+
+// Split a transcript into move tokens: a column letter with its row number,
+// or a pass written as "pa" or "--". Whitespace is ignored.
+fn tokens(transcript: &str) -> Vec<String> {
+    let chars: Vec<char> = transcript.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let first = chars[start];
+        let second = chars.get(start + 1).copied().unwrap_or(' ');
+        let mut end = start + 1;
+        if first == '-' || first.eq_ignore_ascii_case(&'p') && second.eq_ignore_ascii_case(&'a') {
+            end += 1;
+        } else {
+            while end < chars.len() && chars[end].is_ascii_digit() {
+                end += 1;
+            }
+        }
+        let end = end.min(chars.len());
+        tokens.push(chars[start..end].iter().collect());
+        start = end;
+    }
+    tokens
+}
+
+// Why a transcript could not be loaded. Move numbers start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TranscriptError {
+    // The text is not a square name
+    BadSquare { number: usize, text: String },
+    // The square is not a legal move in that position
+    IllegalMove { number: usize, text: String },
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranscriptError::BadSquare { number, text } => {
+                write!(f, "move {}: '{}' is not a square", number, text)
+            }
+            TranscriptError::IllegalMove { number, text } => {
+                write!(f, "move {}: '{}' is not a legal move", number, text)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TranscriptError {}
+
+// The moves played in a game with undo and redo. Every position is kept,
+// so stepping back and forth costs nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History<const N: usize = SIZE> {
+    // positions[i] is the position before moves[i]
+    positions: Vec<Game<N>>,
+    moves: Vec<Move>,
+    // Number of moves currently applied; moves past it can be redone
+    cursor: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new()
+    }
+}
+
+impl History {
+    // An empty history from the standard starting position
+    pub fn new() -> History {
+        History::from_game(Game::new())
+    }
+
+    // Replay a transcript from the standard starting position
+    pub fn from_transcript(transcript: &str) -> Result<History, TranscriptError> {
+        History::from_transcript_at(Game::new(), transcript)
+    }
+}
+
+impl<const N: usize> History<N> {
+    // An empty history from any position
+    pub fn from_game(game: Game<N>) -> History<N> {
+        History { positions: vec![game], moves: Vec::new(), cursor: 0 }
+    }
+
+    // The current position
+    pub fn game(&self) -> &Game<N> {
+        &self.positions[self.cursor]
+    }
+
+    // The position the history started from
+    pub fn start(&self) -> &Game<N> {
+        &self.positions[0]
+    }
+
+    // The moves leading to the current position
+    pub fn moves(&self) -> &[Move] {
+        &self.moves[..self.cursor]
+    }
+
+    // Play a move for the side to move. Any undone moves are discarded.
+    pub fn play(&mut self, row: usize, col: usize) -> bool {
+        self.try_play(row, col).is_ok()
+    }
+
+    // Like `play`, saying why an illegal move was refused
+    pub fn try_play(&mut self, row: usize, col: usize) -> Result<(), MoveError> {
+        let mut next = self.game().clone();
+        next.try_play(row, col)?;
+        self.push(Move::Play(row, col), next);
+        Ok(())
+    }
+
+    // Pass for the side to move if it has no legal move
+    pub fn pass(&mut self) -> bool {
+        let mut next = self.game().clone();
+        if !next.pass() {
+            return false;
+        }
+        self.push(Move::Pass, next);
+        true
+    }
+
+    // Play or pass
+    pub fn apply(&mut self, mv: Move) -> bool {
+        match mv {
+            Move::Play(row, col) => self.play(row, col),
+            Move::Pass => self.pass(),
+        }
+    }
+
+    fn push(&mut self, mv: Move, next: Game<N>) {
+        self.moves.truncate(self.cursor);
+        self.positions.truncate(self.cursor + 1);
+        self.moves.push(mv);
+        self.positions.push(next);
+        self.cursor += 1;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.cursor > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.cursor < self.moves.len()
+    }
+
+    // Take back the last move, returning it
+    pub fn undo(&mut self) -> Option<Move> {
+        if !self.can_undo() {
+            return None;
+        }
+        self.cursor -= 1;
+        Some(self.moves[self.cursor])
+    }
+
+    // Replay the last undone move, returning it
+    pub fn redo(&mut self) -> Option<Move> {
+        if !self.can_redo() {
+            return None;
+        }
+        self.cursor += 1;
+        Some(self.moves[self.cursor - 1])
+    }
+
+    // The moves so far in standard notation, e.g. "f5d6c3". Passes are left
+    // out as they follow from the position.
+    pub fn to_transcript(&self) -> String {
+        self.moves()
+            .iter()
+            .filter_map(|mv| match *mv {
+                Move::Play(row, col) => Some(square_name(row, col)),
+                Move::Pass => None,
+            })
+            .collect()
+    }
+
+    // Replay a transcript from any position. Passes are inserted where the
+    // side to move has no legal move; explicit "pa" or "--" tokens are
+    // accepted there too.
+    pub fn from_transcript_at(game: Game<N>, transcript: &str) -> Result<History<N>, TranscriptError> {
+        let mut history = History::from_game(game);
+
+        for (i, token) in tokens(transcript).into_iter().enumerate() {
+            let number = i + 1;
+            let passed = history.game().must_pass() && history.pass();
+            if token == "--" || token.eq_ignore_ascii_case("pa") {
+                if passed {
+                    continue;
+                }
+                return Err(TranscriptError::IllegalMove { number, text: token });
+            }
+            let Some((row, col)) = parse_square_name_sized(&token, N) else {
+                return Err(TranscriptError::BadSquare { number, text: token });
+            };
+            if !history.play(row, col) {
+                return Err(TranscriptError::IllegalMove { number, text: token });
+            }
+        }
+        if history.game().must_pass() {
+            history.pass();
+        }
+        Ok(history)
+    }
+}
@@ -1,36 +1,110 @@
+pub mod agent;
+pub mod bitboard;
+pub mod book;
+pub mod clock;
+pub mod database;
+pub mod engine;
+pub mod game;
+pub mod history;
+pub mod nboard;
+pub mod net;
+pub mod notation;
+pub mod pattern;
+pub mod perft;
+pub mod review;
+pub mod rules;
+pub mod selfplay;
+pub mod service;
+pub mod solver;
+pub mod tournament;
+pub mod ui;
+pub mod zobrist;
+
+pub use bitboard::Bitboard;
+pub use book::{Book, BookError};
+pub use clock::{Clock, TimeControl};
+pub use database::{Database, DatabaseError};
+pub use engine::{Analysis, Engine, Evaluate, Evaluation, Heuristic, Limit, Variation};
+pub use game::{Cell, Game, LegalMove, MoveError, Player};
+pub use history::{History, Move};
+pub use notation::{format_position, parse_position, PositionError};
+pub use rules::{Goal, Rules, Setup};
+pub use solver::{Solution, Solver};
+
+use game::grid_from_board;
+
 pub const SIZE: usize = 8;
-pub type Board = [[char; SIZE]; SIZE];
+pub type Board<const N: usize = SIZE> = [[char; N]; N];
+
+// Board sizes must be even so the four starting discs sit in the centre, and
+// at most 26 so every row and column has a letter
+pub const fn is_supported_size(size: usize) -> bool {
+    size >= 4 && size <= 26 && size.is_multiple_of(2)
+}
 
 // Parse input (like "cd") into row and column indices
 pub fn parse_input(input: &str) -> Option<(usize, usize)> {
-    if input.len() != 2 {
-        return None;
-    }
+    parse_input_sized(input, SIZE)
+}
+
+// Parse input on a board with `size` rows and columns
+pub fn parse_input_sized(input: &str, size: usize) -> Option<(usize, usize)> {
+    try_parse_input_sized(input, size).ok()
+}
+
+// Parse input like `parse_input`, saying why it is not a move
+pub fn try_parse_input(input: &str) -> Result<(usize, usize), MoveError> {
+    try_parse_input_sized(input, SIZE)
+}
 
-    let row = input.chars().nth(0)?.to_ascii_lowercase();
-    let col = input.chars().nth(1)?.to_ascii_lowercase();
+// Parse input on a board with `size` rows and columns, saying why it is not
+// a move. Letters past the edge of the board are `OffBoard`; anything that
+// is not two letters is `BadInput`.
+pub fn try_parse_input_sized(input: &str, size: usize) -> Result<(usize, usize), MoveError> {
+    let bad_input = || MoveError::BadInput { text: input.to_string() };
+    let chars: Vec<char> = input.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let [row, col] = chars[..] else {
+        return Err(bad_input());
+    };
+    if !row.is_ascii_lowercase() || !col.is_ascii_lowercase() {
+        return Err(bad_input());
+    }
+    let (row, col) = (row as usize - 'a' as usize, col as usize - 'a' as usize);
 
-    // Check if row and column are within 'a' to 'h'
-    if row < 'a' || row > 'h' || col < 'a' || col > 'h' {
-        return None;
+    // Check if row and column are within the first `size` letters
+    if row >= size || col >= size {
+        return Err(MoveError::OffBoard { row, col });
     }
+    Ok((row, col))
+}
 
-    Some((row as usize - 'a' as usize, col as usize - 'a' as usize))
+// Format row and column indices as input (like "cd"), the inverse of parse_input
+pub fn format_move(row: usize, col: usize) -> String {
+    format!("{}{}", (b'a' + row as u8) as char, (b'a' + col as u8) as char)
 }
 
 // Create the initial game board
 pub fn create_initial_board() -> Board {
-    let mut board = [['.'; SIZE]; SIZE];
-    board[3][3] = 'W';
-    board[3][4] = 'B';
-    board[4][3] = 'B';
-    board[4][4] = 'W';
+    create_initial_board_sized()
+}
+
+// Create the initial board of any supported size, with the four starting
+// discs in the centre
+pub fn create_initial_board_sized<const N: usize>() -> Board<N> {
+    const { assert!(is_supported_size(N), "unsupported board size") };
+    let mut board = [['.'; N]; N];
+    let mid = N / 2;
+    board[mid - 1][mid - 1] = 'W';
+    board[mid - 1][mid] = 'B';
+    board[mid][mid - 1] = 'B';
+    board[mid][mid] = 'W';
     board
 }
 
 // Print the board
-pub fn print_board(board: &Board) {
-    println!("  abcdefgh");
+pub fn print_board<const N: usize>(board: &Board<N>) {
+    let header: String = (0..N).map(|i| (b'a' + i as u8) as char).collect();
+    println!("  {}", header);
     for (i, row) in board.iter().enumerate() {
         print!("{} ", (b'a' + i as u8) as char);
         for cell in row.iter() {
@@ -40,114 +114,100 @@ pub fn print_board(board: &Board) {
     }
 }
 
-// Check if a move is valid
-pub fn is_valid_move(board: &Board, player: char, row: usize, col: usize) -> bool {
-    if board[row][col] != '.' {
-        return false;
+// Print the board with '*' on the given squares, e.g. the legal moves
+pub fn print_board_marked<const N: usize>(board: &Board<N>, marks: &[(usize, usize)]) {
+    let mut marked = *board;
+    for &(row, col) in marks {
+        marked[row][col] = '*';
     }
+    print_board(&marked);
+}
 
-    let opponent = if player == 'B' { 'W' } else { 'B' };
-
-    // Direction vectors for N, S, W, E, NW, NE, SW, SE
-    let directions = [
-        (-1, 0), (1, 0), (0, -1), (0, 1),
-        (-1, -1), (-1, 1), (1, -1), (1, 1),
-    ];
-
-    // Check in all directions
-    for &(dr, dc) in &directions {
-        let mut r = row as isize + dr;
-        let mut c = col as isize + dc;
-        let mut found_opponent = false;
-
-        while r >= 0 && r < SIZE as isize && c >= 0 && c < SIZE as isize {
-            let cur = board[r as usize][c as usize];
-            if cur == opponent {
-                found_opponent = true;
-            } else if cur == player && found_opponent {
-                return true;
-            } else {
-                break;
-            }
-            r += dr;
-            c += dc;
-        }
-    }
-    false
+// Check if a move is valid
+pub fn is_valid_move<const N: usize>(board: &Board<N>, player: char, row: usize, col: usize) -> bool {
+    check_move(board, player, row, col).is_ok()
+}
+
+// Check a move like `is_valid_move`, saying why it is not valid
+pub fn check_move<const N: usize>(board: &Board<N>, player: char, row: usize, col: usize) -> Result<(), MoveError> {
+    let player = Player::from_char(player).ok_or(MoveError::BadPlayer { found: player })?;
+    game::check_move(&grid_from_board(board), player, row, col)
 }
 
 // Apply a move and flip opponent pieces
-pub fn apply_move(board: &mut Board, player: char, row: usize, col: usize) {
-    board[row][col] = player;
-
-    let opponent = if player == 'B' { 'W' } else { 'B' };
-
-    let directions = [
-        (-1, 0), (1, 0), (0, -1), (0, 1),
-        (-1, -1), (-1, 1), (1, -1), (1, 1),
-    ];
-
-    for &(dr, dc) in &directions {
-        let mut r = row as isize + dr;
-        let mut c = col as isize + dc;
-        let mut to_flip = Vec::new();
-
-        while r >= 0 && r < SIZE as isize && c >= 0 && c < SIZE as isize {
-            let cur = board[r as usize][c as usize];
-            if cur == opponent {
-                to_flip.push((r as usize, c as usize));
-            } else if cur == player {
-                for (fr, fc) in to_flip {
-                    board[fr][fc] = player;
-                }
-                break;
-            } else {
-                break;
+pub fn apply_move<const N: usize>(board: &mut Board<N>, player: char, row: usize, col: usize) {
+    let mut hash = 0;
+    apply_move_hashed(board, player, row, col, &mut hash);
+}
+
+// Apply a move only if it is valid. Unlike `apply_move`, which places the
+// disc regardless, an invalid move leaves the board untouched.
+pub fn try_apply_move<const N: usize>(board: &mut Board<N>, player: char, row: usize, col: usize) -> Result<(), MoveError> {
+    check_move(board, player, row, col)?;
+    apply_move(board, player, row, col);
+    Ok(())
+}
+
+// Apply a move like `apply_move` and update a Zobrist hash of the board
+// (see `zobrist::hash_board`) to match, including the change of turn
+pub fn apply_move_hashed<const N: usize>(board: &mut Board<N>, player: char, row: usize, col: usize, hash: &mut u64) {
+    let Some(player) = Player::from_char(player) else {
+        return;
+    };
+    let mut grid = grid_from_board(board);
+    let own = game::Cell::from(player);
+    game::place_with(&mut grid, player, row, col, |r, c, old| {
+        let index = r * N + c;
+        *hash ^= zobrist::cell_key(old, index) ^ zobrist::cell_key(own, index);
+    });
+    *hash ^= zobrist::SIDE_KEY;
+
+    // Only write back discs so any other characters on the board survive
+    for (r, cells) in grid.iter().enumerate() {
+        for (c, cell) in cells.iter().enumerate() {
+            if let Some(owner) = cell.player() {
+                board[r][c] = owner.to_char();
             }
-            r += dr;
-            c += dc;
         }
     }
 }
 
 // Check if the player has valid moves
-pub fn has_valid_moves(board: &Board, player: char) -> bool {
-    for row in 0..SIZE {
-        for col in 0..SIZE {
-            if is_valid_move(board, player, row, col) {
-                return true;
-            }
-        }
+pub fn has_valid_moves<const N: usize>(board: &Board<N>, player: char) -> bool {
+    match Player::from_char(player) {
+        Some(player) => game::has_legal_move(&grid_from_board(board), player),
+        None => false,
     }
-    false
 }
 
-// Count the number of black and white pieces
-pub fn count_pieces(board: &Board) -> (usize, usize) {
-    let mut black_count = 0;
-    let mut white_count = 0;
-
-    for row in board.iter() {
-        for &cell in row.iter() {
-            if cell == 'B' {
-                black_count += 1;
-            } else if cell == 'W' {
-                white_count += 1;
-            }
-        }
+// List the valid moves for the player as (row, col, discs flipped)
+pub fn valid_moves<const N: usize>(board: &Board<N>, player: char) -> Vec<(usize, usize, usize)> {
+    match Player::from_char(player) {
+        Some(player) => game::list_moves(&grid_from_board(board), player)
+            .into_iter()
+            .map(|mv| (mv.row, mv.col, mv.flips))
+            .collect(),
+        None => Vec::new(),
     }
-    (black_count, white_count)
+}
+
+// Count the number of black and white pieces
+pub fn count_pieces<const N: usize>(board: &Board<N>) -> (usize, usize) {
+    game::count(&grid_from_board(board))
 }
 
 // Print the winner or if it's a draw
-pub fn print_winner(board: &Board) {
+pub fn print_winner<const N: usize>(board: &Board<N>) {
+    print_winner_for(board, Goal::MostDiscs);
+}
+
+// Print the winner under a variant's goal, e.g. the player with fewer discs
+// in anti-reversi
+pub fn print_winner_for<const N: usize>(board: &Board<N>, goal: Goal) {
     let (black_count, white_count) = count_pieces(board);
 
-    if black_count > white_count {
-        println!("Black wins by {} points!", black_count - white_count);
-    } else if white_count > black_count {
-        println!("White wins by {} points!", white_count - black_count);
-    } else {
-        println!("Draw!");
+    match goal.winner(black_count, white_count) {
+        Some(player) => println!("{} wins by {} points!", player.name(), black_count.abs_diff(white_count)),
+        None => println!("Draw!"),
     }
 }
\ No newline at end of file
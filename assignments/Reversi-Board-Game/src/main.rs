@@ -1,41 +1,807 @@
-use reversi::{create_initial_board, print_board, parse_input, apply_move, print_winner, is_valid_move, has_valid_moves};
+use reversi::clock::format_clock;
+use reversi::rules::{parse_blocked, RulesError};
+use reversi::{print_board, print_board_marked, try_parse_input_sized, format_move, print_winner_for, is_supported_size, Clock, Engine, Game, Goal, History, Limit, Move, Player, Rules, Setup, TimeControl, SIZE};
 
+mod commands;
+
+use clap::{value_parser, Arg, ArgAction, Command};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fs;
 use std::io::{self, Write};
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Options for an interactive game
+struct Config {
+    computer: Option<Player>,   // colour played by the computer, if any
+    rules: Rules,               // variant, starting setup and blocked squares
+    seed: u64,                  // seed for a random start
+    show_moves: bool,           // mark legal squares on every board
+    tui: bool,                  // full-screen interface instead of prompts
+    load: Option<String>,       // transcript to continue from
+    record: Option<String>,     // file to save the transcript to
+    clock: Option<TimeControl>, // time control, if the game is timed
+}
+
+// Picks a move for the computer, or None to pass. The clock is given when
+// the computer is playing on it.
+type ChooseMove<'a, const N: usize> = dyn FnMut(&Game<N>, Option<&Clock>) -> Option<(usize, usize)> + 'a;
 
 fn main() {
-    let mut board = create_initial_board();
-    let mut current_player = 'B';
+    let matches = Command::new("reversi")
+        .about("Play Reversi in the terminal")
+        .arg(
+            Arg::new("size")
+                .long("size")
+                .value_parser(value_parser!(usize))
+                .default_value("8")
+                .help("Number of rows and columns on the board (even, 4 to 26)"),
+        )
+        .arg(
+            Arg::new("play")
+                .long("play")
+                .value_parser(["black", "white"])
+                .help("Play against the computer as this colour"),
+        )
+        .arg(
+            Arg::new("depth")
+                .long("depth")
+                .value_parser(value_parser!(u32))
+                .default_value("6")
+                .help("Search depth of the computer player"),
+        )
+        .arg(
+            Arg::new("time")
+                .long("time")
+                .value_parser(value_parser!(u64))
+                .help("Thinking time per computer move in milliseconds (overrides --depth)"),
+        )
+        .arg(
+            Arg::new("clock")
+                .long("clock")
+                .value_name("CONTROL")
+                .value_parser(TimeControl::parse)
+                .help("Play on a clock, like 5m or 3m+2s for two seconds more per move; running out of time loses"),
+        )
+        .arg(
+            Arg::new("threads")
+                .long("threads")
+                .value_parser(value_parser!(usize))
+                .default_value("1")
+                .help("Search threads for the computer player (0 for one per CPU)"),
+        )
+        .arg(
+            Arg::new("book")
+                .long("book")
+                .value_name("FILE")
+                .help("Opening book for the computer player (see the `book` subcommand)"),
+        )
+        .arg(
+            Arg::new("weights")
+                .long("weights")
+                .value_name("FILE")
+                .help("Pattern weights for the computer player instead of the default evaluation (see the `tune` subcommand)"),
+        )
+        .arg(
+            Arg::new("position")
+                .long("position")
+                .value_name("POSITION")
+                .help("Start from a position: one B, W, '.' or '#' (blocked) per square, then the side to move"),
+        )
+        .arg(
+            Arg::new("variant")
+                .long("variant")
+                .value_parser(["standard", "anti"])
+                .default_value("standard")
+                .help("What wins: most discs (standard) or fewest discs (anti)"),
+        )
+        .arg(
+            Arg::new("start")
+                .long("start")
+                .value_name("SETUP")
+                .conflicts_with("position")
+                .value_parser(Setup::parse)
+                .help("Starting setup: standard, random, or random:N for N random opening moves"),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_parser(value_parser!(u64))
+                .help("Seed for a random start (default: different every game)"),
+        )
+        .arg(
+            Arg::new("blocked")
+                .long("blocked")
+                .value_name("SQUARES")
+                .help("Squares no disc may occupy, like \"aa,hh\""),
+        )
+        .arg(
+            Arg::new("show-moves")
+                .long("show-moves")
+                .action(ArgAction::SetTrue)
+                .help("Mark the legal moves with '*' on every board"),
+        )
+        .arg(
+            Arg::new("tui")
+                .long("tui")
+                .action(ArgAction::SetTrue)
+                .help("Play in a full-screen interface with the arrow keys and Enter"),
+        )
+        .arg(
+            Arg::new("load")
+                .long("load")
+                .value_name("FILE")
+                .help("Continue a game from a transcript file (like \"f5d6c3\"), played from --position if given"),
+        )
+        .arg(
+            Arg::new("record")
+                .long("record")
+                .value_name("FILE")
+                .help("Save the game transcript to a file when the game ends"),
+        )
+        .subcommand(
+            Command::new("analyze")
+                .about("Score every legal move of a position with its expected line of play")
+                .arg(
+                    Arg::new("position")
+                        .long("position")
+                        .value_name("POSITION")
+                        .help("Analyse this position instead of the standard start"),
+                )
+                .arg(
+                    Arg::new("moves")
+                        .long("moves")
+                        .value_name("TRANSCRIPT")
+                        .help("Moves to play first, like \"f5d6c3\""),
+                )
+                .arg(
+                    Arg::new("depth")
+                        .long("depth")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .default_value("8")
+                        .help("Depth to deepen to"),
+                )
+                .arg(
+                    Arg::new("time")
+                        .long("time")
+                        .value_parser(value_parser!(u64))
+                        .help("Deepen for this many milliseconds instead (overrides --depth)"),
+                )
+                .arg(
+                    Arg::new("lines")
+                        .long("lines")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .help("Show only this many of the best moves"),
+                )
+                .arg(
+                    Arg::new("threads")
+                        .long("threads")
+                        .value_parser(value_parser!(usize))
+                        .default_value("1")
+                        .help("Search threads (0 for one per CPU)"),
+                )
+                .arg(
+                    Arg::new("weights")
+                        .long("weights")
+                        .value_name("FILE")
+                        .help("Pattern weights to search with instead of the default evaluation (see the `tune` subcommand)"),
+                ),
+        )
+        .subcommand(
+            Command::new("batch")
+                .about("Play a list of moves without prompts and print each position as JSON")
+                .arg(
+                    Arg::new("file")
+                        .help("File of moves like \"cd ce\" (default: stdin)"),
+                ),
+        )
+        .subcommand(
+            Command::new("bench")
+                .about("Measure search speed with one and several threads")
+                .arg(
+                    Arg::new("depth")
+                        .long("depth")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .default_value("8")
+                        .help("Search depth for every position"),
+                )
+                .arg(
+                    Arg::new("threads")
+                        .long("threads")
+                        .value_parser(value_parser!(usize))
+                        .default_value("0")
+                        .help("Threads for the parallel search (0 for one per CPU)"),
+                )
+                .arg(
+                    Arg::new("weights")
+                        .long("weights")
+                        .value_name("FILE")
+                        .help("Pattern weights to search with instead of the default evaluation (see the `tune` subcommand)"),
+                ),
+        )
+        .subcommand(
+            Command::new("book")
+                .about("Build an opening book from game transcripts")
+                .arg(
+                    Arg::new("games")
+                        .required(true)
+                        .help("File with one game transcript per line, like \"f5d6c3d3c4\""),
+                )
+                .arg(
+                    Arg::new("plies")
+                        .long("plies")
+                        .value_parser(value_parser!(usize))
+                        .default_value("16")
+                        .help("Number of moves of each game to learn"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .value_name("FILE")
+                        .help("Write the book here instead of stdout"),
+                ),
+        )
+        .subcommand(
+            Command::new("db")
+                .about("Store games and see how they went on from any position")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("add")
+                        .about("Add game transcripts to a database file, creating it if needed")
+                        .arg(Arg::new("database").required(true).help("Database file"))
+                        .arg(
+                            Arg::new("games")
+                                .required(true)
+                                .action(ArgAction::Append)
+                                .help("Files with one game transcript per line, like \"f5d6c3d3c4\""),
+                        ),
+                )
+                .subcommand(
+                    Command::new("query")
+                        .about("Show how often each side won from a position and the moves played next")
+                        .arg(Arg::new("database").required(true).help("Database file"))
+                        .arg(
+                            Arg::new("position")
+                                .long("position")
+                                .value_name("POSITION")
+                                .help("Look up this position instead of the standard start"),
+                        )
+                        .arg(
+                            Arg::new("moves")
+                                .long("moves")
+                                .value_name("TRANSCRIPT")
+                                .help("Moves to play first, like \"f5d6c3\""),
+                        )
+                        .arg(
+                            Arg::new("top")
+                                .long("top")
+                                .value_parser(value_parser!(u32).range(1..))
+                                .help("Show only this many of the most played moves"),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("review")
+                .about("Judge every move of a recorded game against the engine's best move")
+                .arg(
+                    Arg::new("record")
+                        .required(true)
+                        .help("Game record saved with --record, or any file with a transcript"),
+                )
+                .arg(
+                    Arg::new("position")
+                        .long("position")
+                        .value_name("POSITION")
+                        .help("Position the game started from, if not the standard start"),
+                )
+                .arg(
+                    Arg::new("depth")
+                        .long("depth")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .default_value("6")
+                        .help("Search depth for each position"),
+                )
+                .arg(
+                    Arg::new("threads")
+                        .long("threads")
+                        .value_parser(value_parser!(usize))
+                        .default_value("1")
+                        .help("Search threads (0 for one per CPU)"),
+                )
+                .arg(
+                    Arg::new("weights")
+                        .long("weights")
+                        .value_name("FILE")
+                        .help("Pattern weights to search with instead of the default evaluation (see the `tune` subcommand)"),
+                ),
+        )
+        .subcommand(
+            Command::new("solve")
+                .about("Find the exact result and best move of an endgame position")
+                .arg(
+                    Arg::new("file")
+                        .required(true)
+                        .help("Position file: 64 squares of B, W, '.' and '#', then the side to move; lines starting with '#' are comments unless they are rows"),
+                ),
+        )
+        .subcommand(
+            Command::new("perft")
+                .about("Count move-generation leaf nodes to check the rules")
+                .arg(
+                    Arg::new("depth")
+                        .required(true)
+                        .value_parser(value_parser!(u32).range(1..))
+                        .help("Number of plies to count to"),
+                )
+                .arg(
+                    Arg::new("position")
+                        .long("position")
+                        .value_name("POSITION")
+                        .help("Count from this position instead of the standard start"),
+                )
+                .arg(
+                    Arg::new("divide")
+                        .long("divide")
+                        .action(ArgAction::SetTrue)
+                        .help("Also show the count below each root move"),
+                )
+                .arg(
+                    Arg::new("slow")
+                        .long("slow")
+                        .action(ArgAction::SetTrue)
+                        .help("Count with the character board functions instead of bitboards"),
+                ),
+        )
+        .subcommand(
+            Command::new("selfplay")
+                .about("Play engine games against itself and save the positions for training")
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .value_name("FILE")
+                        .required(true)
+                        .help("CSV file to write the positions to"),
+                )
+                .arg(
+                    Arg::new("games")
+                        .long("games")
+                        .short('n')
+                        .value_parser(value_parser!(usize))
+                        .default_value("100")
+                        .help("Number of games to play"),
+                )
+                .arg(
+                    Arg::new("depth")
+                        .long("depth")
+                        .value_parser(value_parser!(u32).range(1..))
+                        .default_value("2")
+                        .help("Search depth of the engine"),
+                )
+                .arg(
+                    Arg::new("random")
+                        .long("random")
+                        .value_parser(parse_probability)
+                        .default_value("0.1")
+                        .help("Chance of a random move instead of the engine's choice"),
+                )
+                .arg(
+                    Arg::new("opening")
+                        .long("opening")
+                        .value_parser(value_parser!(usize))
+                        .default_value("4")
+                        .help("Random moves played before the engine takes over"),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .value_parser(value_parser!(u64))
+                        .default_value("1")
+                        .help("Seed for the random moves"),
+                ),
+        )
+        .subcommand(
+            Command::new("tune")
+                .about("Fit pattern evaluation weights to a self-play dataset")
+                .arg(
+                    Arg::new("data")
+                        .required(true)
+                        .help("CSV dataset written by `selfplay`"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .value_name("FILE")
+                        .required(true)
+                        .help("File to write the weights to"),
+                )
+                .arg(
+                    Arg::new("weights")
+                        .long("weights")
+                        .value_name("FILE")
+                        .help("Start from these weights instead of zeros"),
+                )
+                .arg(
+                    Arg::new("epochs")
+                        .long("epochs")
+                        .value_parser(value_parser!(usize))
+                        .default_value("20")
+                        .help("Passes over the dataset"),
+                )
+                .arg(
+                    Arg::new("rate")
+                        .long("rate")
+                        .value_parser(value_parser!(f32))
+                        .default_value("0.01")
+                        .help("Learning rate"),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .value_parser(value_parser!(u64))
+                        .default_value("1")
+                        .help("Seed for the order positions are visited in"),
+                ),
+        )
+        .subcommand(
+            Command::new("serve")
+                .about("Host a game for two network players")
+                .arg(
+                    Arg::new("addr")
+                        .long("addr")
+                        .default_value("127.0.0.1:7878")
+                        .help("Address to listen on"),
+                ),
+        )
+        .subcommand(
+            Command::new("connect")
+                .about("Join a game hosted with `serve`")
+                .arg(
+                    Arg::new("addr")
+                        .long("addr")
+                        .default_value("127.0.0.1:7878")
+                        .help("Address of the server"),
+                )
+                .arg(
+                    Arg::new("name")
+                        .long("name")
+                        .default_value("player")
+                        .help("Name to join with"),
+                ),
+        )
+        .get_matches();
+
+    match matches.subcommand() {
+        Some(("analyze", sub)) => return commands::analyze::run(sub),
+        Some(("batch", sub)) => return commands::batch::run(sub),
+        Some(("bench", sub)) => return commands::bench::run(sub),
+        Some(("book", sub)) => return commands::book::build(sub),
+        Some(("db", sub)) => match sub.subcommand() {
+            Some(("add", sub)) => return commands::database::add(sub),
+            Some(("query", sub)) => return commands::database::query(sub),
+            _ => unreachable!("a db subcommand is required"),
+        },
+        Some(("review", sub)) => return commands::review::run(sub),
+        Some(("solve", sub)) => return commands::solve::run(sub),
+        Some(("perft", sub)) => return commands::perft::run(sub),
+        Some(("selfplay", sub)) => return commands::train::selfplay(sub),
+        Some(("tune", sub)) => return commands::train::tune(sub),
+        Some(("serve", sub)) => return commands::network::serve(sub),
+        Some(("connect", sub)) => return commands::network::connect(sub),
+        _ => {}
+    }
+
+    let size = *matches.get_one::<usize>("size").unwrap();
+    if !is_supported_size(size) {
+        eprintln!("Unsupported board size {}: use an even number from 4 to 26.", size);
+        process::exit(1);
+    }
+
+    // The computer takes whichever colour the human did not pick
+    let computer = matches.get_one::<String>("play").map(|colour| match colour.as_str() {
+        "black" => Player::White,
+        _ => Player::Black,
+    });
+    if computer.is_some() && size != SIZE {
+        eprintln!("The computer player only plays on the {}x{} board.", SIZE, SIZE);
+        process::exit(1);
+    }
+
+    let setup = match matches.get_one::<String>("position") {
+        Some(text) => Setup::Position(text.clone()),
+        None => matches.get_one::<Setup>("start").cloned().unwrap_or_default(),
+    };
+    let blocked = matches.get_one::<String>("blocked").map_or(Ok(Vec::new()), |text| parse_blocked(text, size));
+    let rules = Rules {
+        goal: Goal::parse(matches.get_one::<String>("variant").unwrap()).expect("clap checked the variant"),
+        setup,
+        blocked: blocked.unwrap_or_else(|err| {
+            eprintln!("Invalid rules: {}", err);
+            process::exit(1);
+        }),
+    };
+    if computer.is_some() && !rules.has_standard_play() {
+        eprintln!("The computer player only plays the standard variant without blocked squares.");
+        process::exit(1);
+    }
+    let seed = matches.get_one::<u64>("seed").copied().unwrap_or_else(time_seed);
+    let limit = match matches.get_one::<u64>("time") {
+        Some(&ms) => Limit::Time(Duration::from_millis(ms)),
+        None => Limit::Depth(*matches.get_one::<u32>("depth").unwrap()),
+    };
+    let mut engine = Engine::new(commands::evaluation(&matches), limit);
+    engine.set_threads(*matches.get_one::<usize>("threads").unwrap());
+    if let Some(path) = matches.get_one::<String>("book") {
+        // Vary the book moves from one game to the next
+        engine.set_book(commands::book::load(path), time_seed());
+    }
+
+    let config = Config {
+        computer,
+        rules,
+        seed,
+        show_moves: matches.get_flag("show-moves"),
+        tui: matches.get_flag("tui"),
+        load: matches.get_one::<String>("load").cloned(),
+        record: matches.get_one::<String>("record").cloned(),
+        clock: matches.get_one::<TimeControl>("clock").copied(),
+    };
+
+    // Each board size is its own type, so pick the game loop for it here
+    match size {
+        4 => play::<4>(&config, &mut |_, _| None),
+        6 => play::<6>(&config, &mut |_, _| None),
+        8 => play::<8>(&config, &mut |game, clock| {
+            // On the clock the engine budgets its own remaining time
+            engine.set_limit(match clock {
+                Some(clock) => Limit::Clock {
+                    remaining: clock.remaining(game.to_move()),
+                    increment: clock.control().increment,
+                },
+                None => limit,
+            });
+            engine.choose_move(game)
+        }),
+        10 => play::<10>(&config, &mut |_, _| None),
+        12 => play::<12>(&config, &mut |_, _| None),
+        14 => play::<14>(&config, &mut |_, _| None),
+        16 => play::<16>(&config, &mut |_, _| None),
+        18 => play::<18>(&config, &mut |_, _| None),
+        20 => play::<20>(&config, &mut |_, _| None),
+        22 => play::<22>(&config, &mut |_, _| None),
+        24 => play::<24>(&config, &mut |_, _| None),
+        26 => play::<26>(&config, &mut |_, _| None),
+        _ => unreachable!("board size was checked above"),
+    }
+}
+
+// Play a game on an N x N board. `choose_move` picks the computer's moves.
+fn play<const N: usize>(config: &Config, choose_move: &mut ChooseMove<N>) {
+    let start = config.rules.start(&mut StdRng::seed_from_u64(config.seed)).unwrap_or_else(|err| {
+        match err {
+            RulesError::BadPosition(err) => eprintln!("Invalid position: {}", err),
+            err => eprintln!("Invalid rules: {}", err),
+        }
+        process::exit(1);
+    });
+    if start.goal() == Goal::FewestDiscs && !config.tui {
+        println!("Anti-reversi: the player with fewer discs wins.");
+    }
+    let mut history = match &config.load {
+        Some(path) => load_transcript(start, path),
+        None => History::from_game(start),
+    };
+
+    // The engine only knows the standard rules: variants have no computer
+    // player, and their hints fall back to counting flips
+    let mut no_engine = |_: &Game<N>, _: Option<&Clock>| None;
+    let choose_move: &mut ChooseMove<N> = if config.rules.has_standard_play() { choose_move } else { &mut no_engine };
+
+    let mut clock = config.clock.map(Clock::new);
+    if config.tui {
+        commands::tui::run(&mut history, config.computer, clock.as_mut(), choose_move);
+    } else {
+        prompt_loop(&mut history, config, clock.as_mut(), choose_move);
+    }
+    match clock.as_ref().and_then(Clock::flagged) {
+        Some(player) => println!("{} ran out of time. {} wins!", player.name(), player.opponent().name()),
+        None if history.game().is_over() => print_winner_for(&history.game().board(), history.game().goal()),
+        None => {}
+    }
+
+    if let Some(path) = &config.record {
+        if let Err(err) = fs::write(path, record_text(&history, clock.as_ref())) {
+            eprintln!("Failed to write {}: {}", path, err);
+            process::exit(1);
+        }
+    }
+}
+
+// The transcript, followed in a timed game by comment lines with the time
+// control, both clocks and any loss on time
+fn record_text<const N: usize>(history: &History<N>, clock: Option<&Clock>) -> String {
+    let mut text = history.to_transcript() + "\n";
+    if let Some(clock) = clock {
+        text += &format!("# time control {}\n", clock.control());
+        text += &format!(
+            "# clock black {} white {}\n",
+            format_clock(clock.remaining(Player::Black)),
+            format_clock(clock.remaining(Player::White))
+        );
+        if let Some(player) = clock.flagged() {
+            text += &format!("# {} lost on time\n", player.name());
+        }
+    }
+    text
+}
+
+// Read moves from stdin until the game is over, printing the board each turn
+fn prompt_loop<const N: usize>(
+    history: &mut History<N>,
+    config: &Config,
+    mut clock: Option<&mut Clock>,
+    choose_move: &mut ChooseMove<N>,
+) {
+    let computer = config.computer;
+    let mut mark_moves = false;
 
     loop {
-        print_board(&board);
-        if !has_valid_moves(&board, current_player) {
-            println!("{} player has no valid move.", current_player);
-            current_player = if current_player == 'B' { 'W' } else { 'B' };
-            if !has_valid_moves(&board, current_player) {
-                println!("{} player has no valid move.", current_player);
+        let game = history.game();
+        if config.show_moves || mark_moves {
+            print_board_marked(&game.board(), &game.legal_moves());
+            mark_moves = false;
+        } else {
+            print_board(&game.board());
+        }
+        if let Some(clock) = &clock {
+            println!("{}", clock);
+        }
+        if game.is_over() {
+            println!("{} player has no valid move.", game.to_move());
+            println!("{} player has no valid move.", game.to_move().opponent());
+            break;
+        }
+        if game.must_pass() {
+            println!("{} player has no valid move.", game.to_move());
+            history.pass();
+            continue;
+        }
+
+        // The side to move's clock runs until it plays
+        if let Some(clock) = clock.as_deref_mut() {
+            clock.start(game.to_move());
+        }
+
+        if computer == Some(game.to_move()) {
+            let (row, col) = choose_move(game, clock.as_deref()).expect("side to move has a legal move");
+            if !stop_clock(&mut clock) {
                 break;
             }
+            println!("Computer plays {} for colour {}.", format_move(row, col), game.to_move());
+            history.play(row, col);
             continue;
         }
 
-        print!("Enter move for colour {} (RowCol): ", current_player);
+        print!("Enter move for colour {} (RowCol): ", game.to_move());
         io::stdout().flush().expect("Failed to flush stdout.");
 
         let mut input = String::new();
         io::stdin().read_line(&mut input).expect("Failed to read line");
         let input = input.trim();
+        if clock.as_deref().and_then(Clock::flagged).is_some() {
+            break;
+        }
 
-        if let Some((row, col)) = parse_input(input) {
-            if is_valid_move(&board, current_player, row, col) {
-                apply_move(&mut board, current_player, row, col);
-                current_player = if current_player == 'B' { 'W' } else { 'B' };
-            } else {
-                println!("Invalid move. Try again.");
+        if input == "moves" {
+            let moves: Vec<String> = game
+                .list_moves()
+                .iter()
+                .map(|mv| format!("{} ({})", format_move(mv.row, mv.col), mv.flips))
+                .collect();
+            println!("Valid moves (discs flipped): {}", moves.join(", "));
+            mark_moves = true;
+        } else if input == "hint" {
+            if let Some((row, col)) = hint_move(game, choose_move) {
+                println!("Hint: {}", format_move(row, col));
+            }
+        } else if (input == "undo" || input == "redo") && clock.is_some() {
+            println!("Undo and redo are not available in a timed game.");
+        } else if input == "undo" {
+            if !undo_turn(history, computer) {
+                println!("Nothing to undo.");
+            }
+        } else if input == "redo" {
+            if !redo_turn(history, computer) {
+                println!("Nothing to redo.");
             }
         } else {
-            println!("Invalid input. Try again.");
+            // The reason for a rejected move goes to stderr, so stdout keeps
+            // the usual transcript of the game
+            match try_parse_input_sized(input, N).map(|(row, col)| (row, col, game.check_move(row, col))) {
+                Err(err) => {
+                    println!("Invalid input. Try again.");
+                    eprintln!("{}", err);
+                }
+                Ok((_, _, Err(err))) => {
+                    println!("Invalid move. Try again.");
+                    eprintln!("{}", err);
+                }
+                Ok((row, col, Ok(()))) if stop_clock(&mut clock) => {
+                    history.play(row, col);
+                }
+                Ok(_) => break,
+            }
+        }
+    }
+}
+
+// Stop the running clock, if the game is timed. Returns false if the mover
+// ran out of time.
+fn stop_clock(clock: &mut Option<&mut Clock>) -> bool {
+    clock.as_deref_mut().is_none_or(Clock::stop)
+}
+
+// A suggested move for the side to move. Sizes and variants without an
+// engine fall back to the move flipping the most discs.
+fn hint_move<const N: usize>(game: &Game<N>, choose_move: &mut ChooseMove<N>) -> Option<(usize, usize)> {
+    choose_move(game, None).or_else(|| {
+        let moves = game.list_moves();
+        moves.iter().max_by_key(|mv| (mv.flips, std::cmp::Reverse((mv.row, mv.col)))).map(|mv| (mv.row, mv.col))
+    })
+}
+
+// A chance from 0 to 1, for clap
+fn parse_probability(text: &str) -> Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(chance) if (0.0..=1.0).contains(&chance) => Ok(chance),
+        _ => Err(format!("'{}' is not a number from 0 to 1", text)),
+    }
+}
+
+// A seed that differs from one run to the next
+fn time_seed() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64)
+}
+
+// Read a transcript file and replay it from `start`. Lines starting with
+// '#', like the clock notes of a timed game, are skipped.
+fn load_transcript<const N: usize>(start: Game<N>, path: &str) -> History<N> {
+    let text = fs::read_to_string(path).unwrap_or_else(|err| {
+        eprintln!("Failed to read {}: {}", path, err);
+        process::exit(1);
+    });
+    let text: String = text.lines().filter(|line| !line.trim_start().starts_with('#')).collect();
+    History::from_transcript_at(start, &text).unwrap_or_else(|err| {
+        eprintln!("Invalid transcript in {}: {}", path, err);
+        process::exit(1);
+    })
+}
+
+// Take back moves until it is a human's turn again. Passes and computer
+// moves are undone along with the human move before them.
+fn undo_turn<const N: usize>(history: &mut History<N>, computer: Option<Player>) -> bool {
+    let mut undone = false;
+    while let Some(mv) = history.undo() {
+        undone = true;
+        if mv != Move::Pass && computer != Some(history.game().to_move()) {
+            break;
         }
     }
-    print_winner(&board);
+    undone
 }
 
+// Replay undone moves up to the next human decision
+fn redo_turn<const N: usize>(history: &mut History<N>, computer: Option<Player>) -> bool {
+    let mut redone = false;
+    while history.redo().is_some() {
+        redone = true;
+        let game = history.game();
+        if computer != Some(game.to_move()) && !game.must_pass() {
+            break;
+        }
+    }
+    redone
+}
@@ -0,0 +1,336 @@
+// The NBoard engine protocol, which lets GUIs and match managers drive the
+// engine over stdin and stdout. Every message is one line of text.
+//
+// The GUI sends:
+//   nboard <version>      first line; the engine answers with its name
+//   set depth <n>         search this many plies from now on
+//   set game <ggf>        replace the game with one in GGF, like
+//                         (;GM[Othello]BO[8 ---...--- *]B[F5]W[F6];)
+//   set contempt <n>      accepted and ignored
+//   move <mv>[/eval/time] a move was played, like "F5" or "PA" for a pass
+//   go                    choose a move for the side to move
+//   hint <n>              show the n best moves
+//   ping <n>              answered once every earlier command is done
+//   learn                 accepted; there is nothing to learn
+//   quit                  stop the engine
+//
+// The engine answers with:
+//   set myname <name>
+//   === <mv>/<eval>/<time>          the move chosen by "go"
+//   search <pv> <eval> 0 <depth>    one line per hint, refined each depth
+//   status <text>                   what the engine is doing
+//   pong <n>
+//   learned
+//
+// "go" does not play the move: the GUI sends it back with "move" like any
+// other. Evaluations are from the mover's point of view as a disc margin:
+// exact for a decided game, otherwise the heuristic score scaled down to a
+// rough estimate (see `UNITS_PER_DISC`).
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::time::Instant;
+
+use crate::bitboard::{coords, Bitboard};
+use crate::engine::{Engine, Heuristic, Limit, WIN_SCORE};
+use crate::game::{Game, Player};
+use crate::history::{parse_square_name, History, Move};
+use crate::{Board, SIZE};
+
+// The name the engine gives the GUI
+pub const ENGINE_NAME: &str = "reversi";
+
+// A message from the GUI to the engine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    NBoard(u32),
+    SetDepth(u32),
+    SetGame(String),
+    SetContempt,
+    Move(Move),
+    Go,
+    Hint(usize),
+    Ping(String),
+    Learn,
+    Quit,
+}
+
+// A move in NBoard form: "F5", or "PA" for a pass
+pub fn move_text(mv: Move) -> String {
+    match mv {
+        Move::Play(row, col) => format!("{}{}", (b'A' + col as u8) as char, row + 1),
+        Move::Pass => "PA".to_string(),
+    }
+}
+
+// Read a move in NBoard form, ignoring any "/eval/time" after it
+pub fn parse_move(text: &str) -> Result<Move, String> {
+    let name = text.split('/').next().unwrap_or("");
+    if name.eq_ignore_ascii_case("pa") {
+        return Ok(Move::Pass);
+    }
+    parse_square_name(name).map(|(row, col)| Move::Play(row, col)).ok_or_else(|| format!("bad move '{}'", text))
+}
+
+fn parse_number<T: std::str::FromStr>(word: Option<&str>, what: &str) -> Result<T, String> {
+    let word = word.ok_or_else(|| format!("missing {}", what))?;
+    word.parse().map_err(|_| format!("bad {} '{}'", what, word))
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let line = line.trim();
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+        let mut words = rest.split_whitespace();
+        match command {
+            "nboard" => parse_number(words.next(), "version").map(Command::NBoard),
+            "set" => match words.next() {
+                Some("depth") => match parse_number(words.next(), "depth")? {
+                    0 => Err("depth must be at least 1".to_string()),
+                    depth => Ok(Command::SetDepth(depth)),
+                },
+                Some("game") => Ok(Command::SetGame(rest["game".len()..].trim().to_string())),
+                Some("contempt") => Ok(Command::SetContempt),
+                Some(other) => Err(format!("unknown setting '{}'", other)),
+                None => Err("missing setting".to_string()),
+            },
+            "move" => parse_move(words.next().ok_or("missing move")?).map(Command::Move),
+            "go" => Ok(Command::Go),
+            "hint" => match parse_number(words.next(), "hint count")? {
+                0 => Err("hint count must be at least 1".to_string()),
+                count => Ok(Command::Hint(count)),
+            },
+            "ping" => Ok(Command::Ping(rest.to_string())),
+            "learn" => Ok(Command::Learn),
+            "quit" => Ok(Command::Quit),
+            _ => Err(format!("unknown command '{}'", command)),
+        }
+    }
+}
+
+// The command in the form `parse` reads
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::NBoard(version) => write!(f, "nboard {}", version),
+            Command::SetDepth(depth) => write!(f, "set depth {}", depth),
+            Command::SetGame(ggf) => write!(f, "set game {}", ggf),
+            Command::SetContempt => write!(f, "set contempt 0"),
+            Command::Move(mv) => write!(f, "move {}", move_text(*mv)),
+            Command::Go => write!(f, "go"),
+            Command::Hint(count) => write!(f, "hint {}", count),
+            Command::Ping(id) => write!(f, "ping {}", id),
+            Command::Learn => write!(f, "learn"),
+            Command::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+// The GGF properties of a game record in order, as (name, value) pairs
+fn ggf_properties(ggf: &str) -> Result<Vec<(&str, &str)>, String> {
+    let body = ggf.trim();
+    let body = body.strip_prefix("(;").and_then(|body| body.strip_suffix(";)")).ok_or("a GGF game is written (;...;)")?;
+    let mut properties = Vec::new();
+    let mut rest = body.trim_start();
+    while !rest.is_empty() {
+        let (name, value) = rest.split_once('[').ok_or_else(|| format!("bad GGF near '{}'", rest))?;
+        let (value, after) = value.split_once(']').ok_or("unclosed GGF property")?;
+        properties.push((name.trim(), value));
+        rest = after.trim_start();
+    }
+    Ok(properties)
+}
+
+// The starting position of a GGF "BO" property: the size, the squares row
+// by row ('-' empty, '*' black, 'O' white) and the side to move
+fn ggf_board(text: &str) -> Result<Game, String> {
+    let mut words = text.split_whitespace();
+    if words.next() != Some("8") {
+        return Err(format!("only 8x8 boards are supported, not '{}'", text));
+    }
+    let squares: String = words.clone().take_while(|word| word.len() > 1).collect();
+    let side = words.last().unwrap_or("");
+    if squares.chars().count() != SIZE * SIZE {
+        return Err(format!("a board needs {} squares", SIZE * SIZE));
+    }
+
+    let mut board: Board = [['.'; SIZE]; SIZE];
+    for (i, square) in squares.chars().enumerate() {
+        board[i / SIZE][i % SIZE] = match square {
+            '-' => '.',
+            '*' => 'B',
+            'O' => 'W',
+            _ => return Err(format!("bad square '{}'", square)),
+        };
+    }
+    let to_move = match side {
+        "*" => Player::Black,
+        "O" => Player::White,
+        _ => return Err(format!("bad side to move '{}'", side)),
+    };
+    Ok(Game::from_board(&board, to_move))
+}
+
+// Read a game in GGF, as sent by "set game". Other properties, like the
+// players' names and the clocks, are ignored.
+pub fn parse_ggf(ggf: &str) -> Result<History, String> {
+    let properties = ggf_properties(ggf)?;
+    let start = match properties.iter().find(|(name, _)| *name == "BO") {
+        Some((_, board)) => ggf_board(board)?,
+        None => Game::new(),
+    };
+
+    let mut history = History::from_game(start);
+    for (name, value) in properties {
+        let player = match name {
+            "B" => Player::Black,
+            "W" => Player::White,
+            _ => continue,
+        };
+        let number = history.moves().len() + 1;
+        if player != history.game().to_move() {
+            return Err(format!("move {}: it is not {}'s turn", number, player.name()));
+        }
+        let mv = parse_move(value).map_err(|err| format!("move {}: {}", number, err))?;
+        if !history.apply(mv) {
+            return Err(format!("move {}: {} is not a legal move", number, move_text(mv)));
+        }
+    }
+    Ok(history)
+}
+
+// Heuristic score worth about one disc of final margin. The heuristic gives
+// 10 for each move of mobility and 100 for a corner, so an extra move is
+// taken to be worth a disc and a corner ten.
+pub const UNITS_PER_DISC: i32 = 10;
+
+// A score in NBoard's units: the disc margin for a decided game, otherwise
+// the heuristic score rounded to the nearest estimated disc
+fn eval(score: i32) -> i32 {
+    if score >= WIN_SCORE {
+        score - WIN_SCORE
+    } else if score <= -WIN_SCORE {
+        score + WIN_SCORE
+    } else {
+        (score as f64 / UNITS_PER_DISC as f64).round() as i32
+    }
+}
+
+// The engine's side of a protocol session
+pub struct Session {
+    history: History,
+    engine: Engine,
+}
+
+impl Session {
+    // A session at the standard start, searching `depth` plies
+    pub fn new(depth: u32) -> Session {
+        Session { history: History::new(), engine: Engine::new(Heuristic::default(), Limit::Depth(depth)) }
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    // Carry out one command, writing any answer to `out`. Returns
+    // Ok(false) after "quit", and Err with the reason for a command that
+    // cannot be carried out; the session is then unchanged.
+    pub fn handle(&mut self, command: Command, out: &mut impl Write) -> io::Result<Result<bool, String>> {
+        match command {
+            Command::NBoard(_) => writeln!(out, "set myname {}", ENGINE_NAME)?,
+            Command::SetDepth(depth) => self.engine.set_limit(Limit::Depth(depth)),
+            Command::SetGame(ggf) => match parse_ggf(&ggf) {
+                Ok(history) => self.history = history,
+                Err(reason) => return Ok(Err(reason)),
+            },
+            Command::SetContempt => {}
+            Command::Move(mv) => {
+                if self.history.game().is_over() {
+                    return Ok(Err("the game is over".to_string()));
+                }
+                let legal = match mv {
+                    // MoveError names squares in RowCol form, which GUIs do not use
+                    Move::Play(row, col) => {
+                        self.history.try_play(row, col).map_err(|_| format!("{} is not a legal move", move_text(mv)))
+                    }
+                    Move::Pass if self.history.pass() => Ok(()),
+                    Move::Pass => Err(format!("{} has a legal move and cannot pass", self.history.game().to_move().name())),
+                };
+                if let Err(reason) = legal {
+                    return Ok(Err(reason));
+                }
+            }
+            Command::Go => self.go(out)?,
+            Command::Hint(count) => self.hint(count, out)?,
+            Command::Ping(id) => writeln!(out, "pong {}", id)?,
+            Command::Learn => writeln!(out, "learned")?,
+            Command::Quit => return Ok(Ok(false)),
+        }
+        out.flush()?;
+        Ok(Ok(true))
+    }
+
+    fn go(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let game = self.history.game();
+        let start = Instant::now();
+        writeln!(out, "status Thinking")?;
+        out.flush()?;
+        let (mv, score) = if game.must_pass() || game.is_over() {
+            (Move::Pass, None)
+        } else {
+            let result = self.engine.search(&Bitboard::from_grid(game.grid()), game.to_move());
+            let mv = result.best_move.map(coords).map_or(Move::Pass, |(row, col)| Move::Play(row, col));
+            (mv, Some(result.score))
+        };
+        let eval = score.map_or(String::new(), |score| format!("{:+}", eval(score)));
+        writeln!(out, "status")?;
+        writeln!(out, "=== {}/{}/{:.1}", move_text(mv), eval, start.elapsed().as_secs_f64())
+    }
+
+    fn hint(&self, count: usize, out: &mut impl Write) -> io::Result<()> {
+        let game = self.history.game();
+        if game.is_over() {
+            return Ok(());
+        }
+        writeln!(out, "status Analyzing")?;
+        out.flush()?;
+        // A failed write is kept and reported once the search is done
+        let mut result = Ok(());
+        self.engine.analyze(&Bitboard::from_grid(game.grid()), game.to_move(), |analysis| {
+            for variation in analysis.variations.iter().take(count) {
+                if result.is_err() {
+                    return;
+                }
+                let pv: String = variation.moves.iter().map(|&mv| move_text(mv)).collect();
+                result = writeln!(out, "search {} {:+} 0 {}", pv, eval(variation.score), analysis.depth)
+                    .and_then(|_| out.flush());
+            }
+        });
+        result?;
+        writeln!(out, "status")
+    }
+}
+
+// Talk the protocol until "quit" or the end of the input. Commands that
+// cannot be carried out are reported on `errors` and otherwise ignored, as
+// GUIs expect.
+pub fn run(input: impl BufRead, mut out: impl Write, mut errors: impl Write, depth: u32) -> io::Result<()> {
+    let mut session = Session::new(depth);
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let outcome = match Command::parse(&line) {
+            Ok(command) => session.handle(command, &mut out)?,
+            Err(reason) => Err(reason),
+        };
+        match outcome {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(reason) => writeln!(errors, "{}: {}", line.trim(), reason)?,
+        }
+    }
+    Ok(())
+}
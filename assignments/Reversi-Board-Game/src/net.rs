@@ -0,0 +1,337 @@
+// A line-based protocol for playing a game between two machines over TCP.
+//
+// Every message is one line of text. Clients send:
+//   JOIN <name>     first line on a new connection
+//   MOVE <rc>       play at row r, column c (like "cd")
+//   PASS            pass; only allowed without a legal move
+//   BOARD           ask for the current position
+//   QUIT            leave the game
+//
+// The server answers with:
+//   WELCOME <B|W>   the colour assigned to the client
+//   START           both players have joined
+//   BOARD <pos>     the position in `notation` format, side to move last
+//   MOVED <B|W> <rc>
+//   PASSED <B|W>
+//   ERROR <reason>  the last command was rejected; nothing changed
+//   LEFT <B|W>      a player disconnected; the game is abandoned
+//   GAMEOVER <black> <white>
+//
+// The server owns the game and checks every move, so clients cannot cheat.
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::game::{Game, Player};
+use crate::{format_move, parse_input};
+
+// How long a new connection has to send its JOIN line, and how long that
+// line may be
+const JOIN_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_JOIN_LINE: u64 = 256;
+
+// Pause after a failed accept, so errors that persist, like running out of
+// file descriptors, do not keep the server spinning
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+// A message from a client to the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientMessage {
+    Join(String),
+    Move(usize, usize),
+    Pass,
+    Board,
+    Quit,
+}
+
+// A message from the server to a client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerMessage {
+    Welcome(Player),
+    Start,
+    Board(String),
+    Moved(Player, usize, usize),
+    Passed(Player),
+    Error(String),
+    Left(Player),
+    GameOver(usize, usize),
+}
+
+fn parse_player(word: Option<&str>) -> Result<Player, String> {
+    let word = word.ok_or("missing colour")?;
+    let mut chars = word.chars();
+    match (chars.next().and_then(Player::from_char), chars.next()) {
+        (Some(player), None) => Ok(player),
+        _ => Err(format!("bad colour '{}'", word)),
+    }
+}
+
+fn parse_square(word: Option<&str>) -> Result<(usize, usize), String> {
+    let word = word.ok_or("missing square")?;
+    parse_input(word).ok_or_else(|| format!("bad square '{}'", word))
+}
+
+fn parse_count(word: Option<&str>) -> Result<usize, String> {
+    let word = word.ok_or("missing disc count")?;
+    word.parse().map_err(|_| format!("bad disc count '{}'", word))
+}
+
+impl ClientMessage {
+    pub fn parse(line: &str) -> Result<ClientMessage, String> {
+        let line = line.trim();
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+        let mut words = rest.split_whitespace();
+        match command.to_ascii_uppercase().as_str() {
+            "JOIN" if !rest.is_empty() => Ok(ClientMessage::Join(rest.to_string())),
+            "JOIN" => Err("missing name".to_string()),
+            "MOVE" => parse_square(words.next()).map(|(row, col)| ClientMessage::Move(row, col)),
+            "PASS" => Ok(ClientMessage::Pass),
+            "BOARD" => Ok(ClientMessage::Board),
+            "QUIT" => Ok(ClientMessage::Quit),
+            _ => Err(format!("unknown command '{}'", command)),
+        }
+    }
+}
+
+impl fmt::Display for ClientMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientMessage::Join(name) => write!(f, "JOIN {}", name),
+            ClientMessage::Move(row, col) => write!(f, "MOVE {}", format_move(*row, *col)),
+            ClientMessage::Pass => write!(f, "PASS"),
+            ClientMessage::Board => write!(f, "BOARD"),
+            ClientMessage::Quit => write!(f, "QUIT"),
+        }
+    }
+}
+
+impl ServerMessage {
+    pub fn parse(line: &str) -> Result<ServerMessage, String> {
+        let line = line.trim();
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+        let mut words = rest.split_whitespace();
+        match command {
+            "WELCOME" => parse_player(words.next()).map(ServerMessage::Welcome),
+            "START" => Ok(ServerMessage::Start),
+            "BOARD" => Ok(ServerMessage::Board(rest.to_string())),
+            "MOVED" => {
+                let player = parse_player(words.next())?;
+                let (row, col) = parse_square(words.next())?;
+                Ok(ServerMessage::Moved(player, row, col))
+            }
+            "PASSED" => parse_player(words.next()).map(ServerMessage::Passed),
+            "ERROR" => Ok(ServerMessage::Error(rest.to_string())),
+            "LEFT" => parse_player(words.next()).map(ServerMessage::Left),
+            "GAMEOVER" => {
+                let black = parse_count(words.next())?;
+                let white = parse_count(words.next())?;
+                Ok(ServerMessage::GameOver(black, white))
+            }
+            _ => Err(format!("unknown message '{}'", command)),
+        }
+    }
+}
+
+impl fmt::Display for ServerMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerMessage::Welcome(player) => write!(f, "WELCOME {}", player),
+            ServerMessage::Start => write!(f, "START"),
+            ServerMessage::Board(position) => write!(f, "BOARD {}", position),
+            ServerMessage::Moved(player, row, col) => write!(f, "MOVED {} {}", player, format_move(*row, *col)),
+            ServerMessage::Passed(player) => write!(f, "PASSED {}", player),
+            ServerMessage::Error(reason) => write!(f, "ERROR {}", reason),
+            ServerMessage::Left(player) => write!(f, "LEFT {}", player),
+            ServerMessage::GameOver(black, white) => write!(f, "GAMEOVER {} {}", black, white),
+        }
+    }
+}
+
+fn send_line(stream: &mut TcpStream, message: &impl fmt::Display) -> io::Result<()> {
+    writeln!(stream, "{}", message)?;
+    stream.flush()
+}
+
+// How a hosted game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    // Neither side could move; final black and white disc counts
+    Finished(usize, usize),
+    // A player quit or disconnected first
+    Abandoned(Player),
+}
+
+// Read a client's JOIN and welcome it. Returns None if it sent anything
+// else, after telling it why. A client that is silent for `JOIN_TIMEOUT`
+// gets an error, so it cannot hold up the players after it.
+fn join(mut stream: TcpStream, player: Player) -> io::Result<Option<(TcpStream, BufReader<TcpStream>, String)>> {
+    stream.set_read_timeout(Some(JOIN_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    if (&mut reader).take(MAX_JOIN_LINE).read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    // A line cut off by the length limit is not a complete message
+    let message = if line.ends_with('\n') { ClientMessage::parse(&line) } else { Err("line too long".to_string()) };
+    match message {
+        Ok(ClientMessage::Join(name)) => {
+            // Players may think as long as they like once seated
+            stream.set_read_timeout(None)?;
+            send_line(&mut stream, &ServerMessage::Welcome(player))?;
+            Ok(Some((stream, reader, name)))
+        }
+        _ => {
+            let _ = send_line(&mut stream, &ServerMessage::Error("expected JOIN <name>".to_string()));
+            Ok(None)
+        }
+    }
+}
+
+// Wait for a client that introduces itself with JOIN. Connections that send
+// anything else are told why and dropped, as are ones that fail or time out
+// before joining; a failed accept is logged and retried after a pause.
+fn accept_player(listener: &TcpListener, player: Player) -> (TcpStream, BufReader<TcpStream>, String) {
+    loop {
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                eprintln!("Failed to accept a connection: {}", err);
+                thread::sleep(ACCEPT_BACKOFF);
+                continue;
+            }
+        };
+        if let Ok(Some(joined)) = join(stream, player) {
+            return joined;
+        }
+    }
+}
+
+// Host one game on the listener: the first client to join plays Black, the
+// second White. Returns when the game is over or a player leaves.
+pub fn serve(listener: &TcpListener) -> io::Result<Outcome> {
+    let (black, black_reader, _) = accept_player(listener, Player::Black);
+    let (white, white_reader, _) = accept_player(listener, Player::White);
+    let mut streams = [black, white];
+
+    // One thread per connection forwards lines to the game loop; None marks
+    // a closed connection
+    let (sender, receiver) = mpsc::channel::<(Player, Option<String>)>();
+    for (player, reader) in [(Player::Black, black_reader), (Player::White, white_reader)] {
+        let sender = sender.clone();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                match line {
+                    Ok(line) => {
+                        if sender.send((player, Some(line))).is_err() {
+                            return;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+            let _ = sender.send((player, None));
+        });
+    }
+    drop(sender);
+
+    let index = |player: Player| match player {
+        Player::Black => 0,
+        Player::White => 1,
+    };
+    let broadcast = |streams: &mut [TcpStream; 2], message: &ServerMessage| {
+        for stream in streams.iter_mut() {
+            // A failed write shows up as a closed connection on the reader side
+            let _ = send_line(stream, message);
+        }
+    };
+
+    let mut game = Game::new();
+    broadcast(&mut streams, &ServerMessage::Start);
+    broadcast(&mut streams, &ServerMessage::Board(game.to_position()));
+
+    while let Ok((player, line)) = receiver.recv() {
+        let message = match line.as_deref().map(ClientMessage::parse) {
+            None | Some(Ok(ClientMessage::Quit)) => {
+                broadcast(&mut streams, &ServerMessage::Left(player));
+                return Ok(Outcome::Abandoned(player));
+            }
+            Some(Err(reason)) => {
+                let _ = send_line(&mut streams[index(player)], &ServerMessage::Error(reason));
+                continue;
+            }
+            Some(Ok(message)) => message,
+        };
+
+        let reply = match message {
+            ClientMessage::Board => Ok(ServerMessage::Board(game.to_position())),
+            ClientMessage::Join(_) => Err("already joined".to_string()),
+            _ if game.to_move() != player => Err("not your turn".to_string()),
+            ClientMessage::Move(row, col) => {
+                match game.try_play(row, col) {
+                    Ok(()) => Ok(ServerMessage::Moved(player, row, col)),
+                    Err(err) => Err(format!("illegal move {}: {}", format_move(row, col), err)),
+                }
+            }
+            ClientMessage::Pass => {
+                if game.pass() {
+                    Ok(ServerMessage::Passed(player))
+                } else {
+                    Err("you have a legal move".to_string())
+                }
+            }
+            ClientMessage::Quit => unreachable!("handled above"),
+        };
+
+        match reply {
+            Ok(ServerMessage::Board(position)) => {
+                let _ = send_line(&mut streams[index(player)], &ServerMessage::Board(position));
+            }
+            Ok(event) => {
+                broadcast(&mut streams, &event);
+                broadcast(&mut streams, &ServerMessage::Board(game.to_position()));
+                if game.is_over() {
+                    let (black, white) = game.score();
+                    broadcast(&mut streams, &ServerMessage::GameOver(black, white));
+                    return Ok(Outcome::Finished(black, white));
+                }
+            }
+            Err(reason) => {
+                let _ = send_line(&mut streams[index(player)], &ServerMessage::Error(reason));
+            }
+        }
+    }
+    unreachable!("reader threads always report a closed connection")
+}
+
+// The client end of a connection
+pub struct Client {
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+}
+
+impl Client {
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Client> {
+        let stream = TcpStream::connect(addr)?;
+        let reader = BufReader::new(stream.try_clone()?);
+        Ok(Client { stream, reader })
+    }
+
+    pub fn send(&mut self, message: &ClientMessage) -> io::Result<()> {
+        send_line(&mut self.stream, message)
+    }
+
+    // Wait for the next message from the server
+    pub fn recv(&mut self) -> io::Result<ServerMessage> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "server closed the connection"));
+        }
+        ServerMessage::parse(&line).map_err(|reason| io::Error::new(io::ErrorKind::InvalidData, reason))
+    }
+}
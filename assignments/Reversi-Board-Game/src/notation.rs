@@ -0,0 +1,110 @@
+use std::fmt;
+
+use crate::game::{Game, Player};
+use crate::{format_move, Board};
+
+// Why a position string could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PositionError {
+    // Fewer characters than there are squares
+    TooShort { expected: usize, found: usize },
+    // All squares are there but the side to move is not
+    MissingSide,
+    // Extra characters after the side to move
+    TooLong { expected: usize, found: usize },
+    // A square is not 'B', 'W', '.' or '#'; `index` counts from 0 in
+    // row-major order
+    BadSquare { index: usize, name: String, found: char },
+    // The side to move is not 'B' or 'W'
+    BadSide { found: char },
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PositionError::TooShort { expected, found } => {
+                write!(f, "expected {} squares and a side to move, found only {} characters", expected, found)
+            }
+            PositionError::MissingSide => write!(f, "missing the side to move ('B' or 'W') after the squares"),
+            PositionError::TooLong { expected, found } => {
+                write!(f, "expected {} characters, found {}", expected, found)
+            }
+            PositionError::BadSquare { index, name, found } => {
+                write!(f, "square {} ({}) is '{}', expected 'B', 'W', '.' or '#'", index, name, found)
+            }
+            PositionError::BadSide { found } => {
+                write!(f, "side to move is '{}', expected 'B' or 'W'", found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+// Parse a position on the standard board: 64 characters of 'B', 'W' or '.'
+// (or '#' for a blocked square) in row-major order followed by the side to
+// move, e.g.
+// "...........................WB......BW........................... B".
+// Whitespace anywhere is ignored, so the squares may also be written as
+// eight rows of eight.
+pub fn parse_position(text: &str) -> Result<(Board, Player), PositionError> {
+    parse_position_sized(text)
+}
+
+// Parse a position on an N x N board
+pub fn parse_position_sized<const N: usize>(text: &str) -> Result<(Board<N>, Player), PositionError> {
+    let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let squares = N * N;
+    match chars.len() {
+        len if len < squares => return Err(PositionError::TooShort { expected: squares, found: len }),
+        len if len == squares => return Err(PositionError::MissingSide),
+        len if len > squares + 1 => return Err(PositionError::TooLong { expected: squares + 1, found: len }),
+        _ => {}
+    }
+
+    let mut board = [['.'; N]; N];
+    for (index, &found) in chars[..squares].iter().enumerate() {
+        let (row, col) = (index / N, index % N);
+        if !matches!(found, 'B' | 'W' | '.' | '#') {
+            return Err(PositionError::BadSquare { index, name: format_move(row, col), found });
+        }
+        board[row][col] = found;
+    }
+
+    let side = chars[squares];
+    let player = Player::from_char(side).ok_or(PositionError::BadSide { found: side })?;
+    Ok((board, player))
+}
+
+// Write a position in the same notation, with a space before the side to move
+pub fn format_position<const N: usize>(board: &Board<N>, player: Player) -> String {
+    let mut text: String = board.iter().flatten().collect();
+    text.push(' ');
+    text.push(player.to_char());
+    text
+}
+
+impl Game {
+    // Start a game from a position string on the standard board
+    pub fn from_position(text: &str) -> Result<Game, PositionError> {
+        Game::from_position_sized(text)
+    }
+}
+
+impl<const N: usize> Game<N> {
+    // Start a game from a position string on an N x N board
+    pub fn from_position_sized(text: &str) -> Result<Game<N>, PositionError> {
+        let (board, player) = parse_position_sized(text)?;
+        Ok(Game::from_board(&board, player))
+    }
+
+    // The position and side to move as a position string
+    pub fn to_position(&self) -> String {
+        format_position(&self.board(), self.to_move())
+    }
+}
+
+// The standard starting position in this notation
+pub fn initial_position() -> String {
+    format_position(&crate::create_initial_board(), Player::Black)
+}
@@ -0,0 +1,207 @@
+// A pattern-based evaluation and a tuner that fits it to self-play data.
+//
+// A pattern is a fixed list of squares. Each arrangement of empty, own and
+// opponent discs on them has its own weight. The pattern is read under all
+// eight symmetries of the board, so every edge and corner is seen twice,
+// once from each side, and symmetric positions get the same value. The
+// evaluation is the sum of the weights of the arrangements read, fitted to
+// predict the final disc difference.
+//
+// Weight files have one line per pattern: its name, then every weight.
+// Lines starting with '#' are comments.
+
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::bitboard::{square, transform_square, Bitboard, SYMMETRIES};
+use crate::engine::Evaluate;
+use crate::game::Player;
+use crate::selfplay::Sample;
+
+// The patterns by name, with their squares in the top left copy
+const PATTERNS: [(&str, &[(usize, usize)]); 2] = [
+    ("edge", &[(0, 0), (0, 1), (0, 2), (0, 3), (0, 4), (0, 5), (0, 6), (0, 7)]),
+    ("corner", &[(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2), (2, 0), (2, 1), (2, 2)]),
+];
+
+// Predicted disc differences are scaled up so the engine sees integers
+const SCALE: f32 = 100.0;
+
+fn table_size(pattern: usize) -> usize {
+    3usize.pow(PATTERNS[pattern].1.len() as u32)
+}
+
+// (pattern, arrangement index) for every symmetric image of every pattern,
+// from the point of view of `player`
+fn features(board: &Bitboard, player: Player) -> Vec<(usize, usize)> {
+    let own = board.discs(player);
+    let opp = board.discs(player.opponent());
+    let mut features = Vec::with_capacity(PATTERNS.len() * SYMMETRIES);
+    for (pattern, (_, squares)) in PATTERNS.iter().enumerate() {
+        for symmetry in 0..SYMMETRIES {
+            let index = squares.iter().fold(0, |index, &(row, col)| {
+                let bit = 1u64 << transform_square(square(row, col), symmetry);
+                let state = if own & bit != 0 { 1 } else if opp & bit != 0 { 2 } else { 0 };
+                index * 3 + state
+            });
+            features.push((pattern, index));
+        }
+    }
+    features
+}
+
+// Weights for every arrangement of every pattern
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternEval {
+    weights: Vec<Vec<f32>>,
+}
+
+impl Default for PatternEval {
+    fn default() -> Self {
+        PatternEval::new()
+    }
+}
+
+impl PatternEval {
+    // All weights zero
+    pub fn new() -> PatternEval {
+        PatternEval { weights: (0..PATTERNS.len()).map(|pattern| vec![0.0; table_size(pattern)]).collect() }
+    }
+
+    // Predicted final disc difference for `player`
+    pub fn predict(&self, board: &Bitboard, player: Player) -> f32 {
+        features(board, player)
+            .into_iter()
+            .map(|(pattern, index)| self.weights[pattern][index])
+            .sum()
+    }
+
+    // Read weights in the file format described above
+    pub fn parse(text: &str) -> Result<PatternEval, WeightsError> {
+        let mut eval = PatternEval::new();
+        let mut seen = vec![false; PATTERNS.len()];
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |reason: String| WeightsError { line: i + 1, reason };
+            let mut words = line.split_whitespace();
+            let name = words.next().unwrap_or("");
+            let Some(pattern) = PATTERNS.iter().position(|&(known, _)| known == name) else {
+                return Err(error(format!("unknown pattern '{}'", name)));
+            };
+            let weights = words
+                .map(|word| word.parse::<f32>().map_err(|_| error(format!("bad weight '{}'", word))))
+                .collect::<Result<Vec<f32>, _>>()?;
+            if weights.len() != table_size(pattern) {
+                return Err(error(format!("expected {} weights, found {}", table_size(pattern), weights.len())));
+            }
+            eval.weights[pattern] = weights;
+            seen[pattern] = true;
+        }
+        match seen.iter().position(|&seen| !seen) {
+            Some(pattern) => Err(WeightsError { line: 0, reason: format!("missing pattern '{}'", PATTERNS[pattern].0) }),
+            None => Ok(eval),
+        }
+    }
+}
+
+impl Evaluate for PatternEval {
+    fn evaluate(&self, board: &Bitboard, player: Player) -> i32 {
+        (self.predict(board, player) * SCALE).round() as i32
+    }
+
+    fn name(&self) -> &'static str {
+        "pattern"
+    }
+}
+
+// The weights in the file format described above
+impl fmt::Display for PatternEval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (pattern, weights) in self.weights.iter().enumerate() {
+            write!(f, "{}", PATTERNS[pattern].0)?;
+            for weight in weights {
+                write!(f, " {}", weight)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// Why a weights file could not be read. Line 0 means the file as a whole.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeightsError {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for WeightsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            0 => write!(f, "{}", self.reason),
+            line => write!(f, "line {}: {}", line, self.reason),
+        }
+    }
+}
+
+impl std::error::Error for WeightsError {}
+
+// Settings for fitting weights
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TuneSettings {
+    // Passes over the whole dataset
+    pub epochs: usize,
+    // Step size of stochastic gradient descent
+    pub learning_rate: f32,
+    // Seed for the order samples are visited in
+    pub seed: u64,
+}
+
+impl Default for TuneSettings {
+    fn default() -> Self {
+        TuneSettings { epochs: 20, learning_rate: 0.01, seed: 1 }
+    }
+}
+
+// Mean squared error of the predictions over the samples, in discs squared
+pub fn mean_squared_error(eval: &PatternEval, samples: &[Sample]) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let total: f64 = samples
+        .iter()
+        .map(|sample| {
+            let error = eval.predict(&sample.board, sample.to_move) - sample.mover_result() as f32;
+            (error as f64).powi(2)
+        })
+        .sum();
+    total / samples.len() as f64
+}
+
+// Fit weights to the samples by stochastic gradient descent on the squared
+// error, starting from `eval`. `progress` is called after each epoch with
+// its number and the mean squared error.
+pub fn tune(mut eval: PatternEval, samples: &[Sample], settings: TuneSettings, mut progress: impl FnMut(usize, f64)) -> PatternEval {
+    let mut rng = StdRng::seed_from_u64(settings.seed);
+    let mut order: Vec<usize> = (0..samples.len()).collect();
+    for epoch in 1..=settings.epochs {
+        order.shuffle(&mut rng);
+        for &i in &order {
+            let sample = &samples[i];
+            let features = features(&sample.board, sample.to_move);
+            let predicted: f32 = features.iter().map(|&(pattern, index)| eval.weights[pattern][index]).sum();
+            let step = settings.learning_rate * (predicted - sample.mover_result() as f32);
+            for (pattern, index) in features {
+                eval.weights[pattern][index] -= step;
+            }
+        }
+        progress(epoch, mean_squared_error(&eval, samples));
+    }
+    eval
+}
@@ -0,0 +1,85 @@
+// Perft: count the leaf nodes of the game tree to a fixed depth. The counts
+// for well-known positions are fixed, so comparing them catches mistakes
+// in move generation and flipping that ordinary games rarely hit.
+//
+// A pass uses up a ply like a move. A finished game counts as a single
+// leaf however much depth is left.
+
+use crate::bitboard::{squares, Bitboard};
+use crate::game::Player;
+use crate::history::Move;
+use crate::{apply_move, has_valid_moves, is_valid_move, Board};
+
+// Known counts from the standard starting position for depths 1 to 11
+pub const START_COUNTS: [u64; 11] = [4, 12, 56, 244, 1396, 8200, 55092, 390216, 3005288, 24571284, 212258800];
+
+// Leaf count with the bitboard move generator
+pub fn perft(board: &Bitboard, player: Player, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = board.legal_moves(player);
+    if moves == 0 {
+        if board.legal_moves(player.opponent()) == 0 {
+            return 1;
+        }
+        return perft(board, player.opponent(), depth - 1);
+    }
+    if depth == 1 {
+        return moves.count_ones() as u64;
+    }
+    squares(moves)
+        .map(|sq| {
+            let mut child = *board;
+            child.apply(player, sq);
+            perft(&child, player.opponent(), depth - 1)
+        })
+        .sum()
+}
+
+// Leaf counts below each move of the root position, for tracking down which
+// line a wrong total comes from
+pub fn divide(board: &Bitboard, player: Player, depth: u32) -> Vec<(Move, u64)> {
+    let moves = board.legal_moves(player);
+    if depth == 0 || moves == 0 && board.legal_moves(player.opponent()) == 0 {
+        return Vec::new();
+    }
+    if moves == 0 {
+        return vec![(Move::Pass, perft(board, player.opponent(), depth - 1))];
+    }
+    squares(moves)
+        .map(|sq| {
+            let mut child = *board;
+            child.apply(player, sq);
+            let (row, col) = crate::bitboard::coords(sq);
+            (Move::Play(row, col), perft(&child, player.opponent(), depth - 1))
+        })
+        .collect()
+}
+
+// Leaf count using only the character board functions (`is_valid_move`,
+// `apply_move` and `has_valid_moves`). Much slower than `perft`, but it
+// checks the code the interactive game relies on, on any board size.
+pub fn perft_board<const N: usize>(board: &Board<N>, player: char, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let opponent = if player == 'B' { 'W' } else { 'B' };
+    if !has_valid_moves(board, player) {
+        if !has_valid_moves(board, opponent) {
+            return 1;
+        }
+        return perft_board(board, opponent, depth - 1);
+    }
+    let mut total = 0;
+    for row in 0..N {
+        for col in 0..N {
+            if is_valid_move(board, player, row, col) {
+                let mut child = *board;
+                apply_move(&mut child, player, row, col);
+                total += perft_board(&child, opponent, depth - 1);
+            }
+        }
+    }
+    total
+}
@@ -0,0 +1,163 @@
+// Post-mortem review of a game. Every position where a player had a choice
+// is searched, and the move played is compared with the engine's best move:
+// the difference between their scores is the move's loss, from which it is
+// judged a good move or an inaccuracy, mistake or blunder.
+//
+// Losses are in the engine's evaluation units, where a disc counts 1 and a
+// corner 100, so giving away a corner is a blunder. Annotated transcripts
+// mark judged moves the usual way: "?!" for an inaccuracy, "?" for a
+// mistake and "??" for a blunder.
+
+use std::fmt;
+
+use crate::bitboard::Bitboard;
+use crate::engine::{Engine, Evaluate};
+use crate::game::Player;
+use crate::history::{square_name, History, Move};
+
+// Smallest losses for each judgement
+pub const INACCURACY: i32 = 20;
+pub const MISTAKE: i32 = 50;
+pub const BLUNDER: i32 = 100;
+
+// How good a move was compared with the best one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Verdict {
+    // As good as the engine's choice
+    Best,
+    // Worse, but by less than an inaccuracy
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl Verdict {
+    // The judgement of a move that scores `loss` below the best
+    pub fn from_loss(loss: i32) -> Verdict {
+        match loss {
+            loss if loss <= 0 => Verdict::Best,
+            loss if loss < INACCURACY => Verdict::Good,
+            loss if loss < MISTAKE => Verdict::Inaccuracy,
+            loss if loss < BLUNDER => Verdict::Mistake,
+            _ => Verdict::Blunder,
+        }
+    }
+
+    // The mark added after the move in an annotated transcript
+    pub fn glyph(self) -> &'static str {
+        match self {
+            Verdict::Best | Verdict::Good => "",
+            Verdict::Inaccuracy => "?!",
+            Verdict::Mistake => "?",
+            Verdict::Blunder => "??",
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Best => write!(f, "best"),
+            Verdict::Good => write!(f, "good"),
+            Verdict::Inaccuracy => write!(f, "inaccuracy"),
+            Verdict::Mistake => write!(f, "mistake"),
+            Verdict::Blunder => write!(f, "blunder"),
+        }
+    }
+}
+
+// The review of one move. Scores are from the mover's point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveReview {
+    // Position of the move in the transcript, starting at 1; passes are
+    // not counted
+    pub number: usize,
+    pub player: Player,
+    pub played: (usize, usize),
+    pub played_score: i32,
+    pub best: (usize, usize),
+    pub best_score: i32,
+    // Number of legal moves the player had
+    pub choices: usize,
+}
+
+impl MoveReview {
+    // How much worse the move scored than the best one
+    pub fn loss(&self) -> i32 {
+        (self.best_score - self.played_score).max(0)
+    }
+
+    pub fn verdict(&self) -> Verdict {
+        Verdict::from_loss(self.loss())
+    }
+}
+
+// Review every move of a game on the 8x8 board with `engine`. Moves with no
+// alternative are judged best without a search. `progress` is called with
+// the number of moves reviewed so far and the total.
+pub fn review<E: Evaluate>(history: &History, engine: &Engine<E>, mut progress: impl FnMut(usize, usize)) -> Vec<MoveReview> {
+    let total = history.moves().iter().filter(|&&mv| mv != Move::Pass).count();
+    let mut game = history.start().clone();
+    let mut reviews = Vec::new();
+    for &mv in history.moves() {
+        let Move::Play(row, col) = mv else {
+            game.pass();
+            continue;
+        };
+        let choices = game.legal_moves().len();
+        let review = if choices == 1 {
+            MoveReview {
+                number: reviews.len() + 1,
+                player: game.to_move(),
+                played: (row, col),
+                played_score: 0,
+                best: (row, col),
+                best_score: 0,
+                choices,
+            }
+        } else {
+            let analysis = engine.analyze(&Bitboard::from_grid(game.grid()), game.to_move(), |_| {});
+            let score_of = |mv: Move| {
+                analysis.variations.iter().find(|variation| variation.moves[0] == mv).map(|variation| variation.score)
+            };
+            let best = analysis.variations[0].moves[0];
+            let Move::Play(best_row, best_col) = best else {
+                unreachable!("a position with legal moves has no pass at the root")
+            };
+            MoveReview {
+                number: reviews.len() + 1,
+                player: game.to_move(),
+                played: (row, col),
+                played_score: score_of(mv).expect("every legal move is analysed"),
+                best: (best_row, best_col),
+                best_score: analysis.variations[0].score,
+                choices,
+            }
+        };
+        reviews.push(review);
+        progress(reviews.len(), total);
+        game.play(row, col);
+    }
+    reviews
+}
+
+// The game in standard notation with a space between moves and the marks
+// of inaccuracies, mistakes and blunders, e.g. "f5 d6 c3?? d3"
+pub fn annotated_transcript(reviews: &[MoveReview]) -> String {
+    let moves: Vec<String> = reviews
+        .iter()
+        .map(|review| square_name(review.played.0, review.played.1) + review.verdict().glyph())
+        .collect();
+    moves.join(" ")
+}
+
+// Number of each player's moves with each verdict, indexed by the verdict
+pub fn tally(reviews: &[MoveReview], player: Player) -> [usize; 5] {
+    let mut counts = [0; 5];
+    for review in reviews.iter().filter(|review| review.player == player) {
+        counts[review.verdict() as usize] += 1;
+    }
+    counts
+}
+
@@ -0,0 +1,187 @@
+// Rule variants. A game is set by three choices:
+//
+//   goal     standard  most discs wins
+//            anti      fewest discs wins (anti-reversi)
+//   start    standard  the usual four discs in the centre
+//            random    a few random legal moves from the usual start;
+//                      "random:N" plays N of them
+//            or any position given in the position notation
+//   blocked  squares no disc may ever occupy, e.g. "aa,hh" in RowCol form;
+//            '#' marks them in positions
+//
+// Blocked squares stop a line of discs like an empty square does, but no
+// move may be played on them: `game` checks the cell before a move, and
+// `Bitboard` keeps them in a mask of their own that its move generation
+// leaves out.
+
+use std::fmt;
+
+use rand::Rng;
+
+use crate::game::{Cell, Game, Player};
+use crate::notation::{parse_position_sized, PositionError};
+use crate::{format_move, try_parse_input_sized, MoveError};
+
+// Random moves played by a "random" start
+pub const RANDOM_PLIES: usize = 4;
+
+// What wins the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Goal {
+    #[default]
+    MostDiscs,
+    FewestDiscs,
+}
+
+impl Goal {
+    // Read "standard" or "anti"
+    pub fn parse(text: &str) -> Result<Goal, RulesError> {
+        match text {
+            "standard" => Ok(Goal::MostDiscs),
+            "anti" => Ok(Goal::FewestDiscs),
+            _ => Err(RulesError::BadGoal { text: text.to_string() }),
+        }
+    }
+
+    // The winner with these disc counts, or None for a draw
+    pub fn winner(self, black: usize, white: usize) -> Option<Player> {
+        let (more, fewer) = match black.cmp(&white) {
+            std::cmp::Ordering::Greater => (Player::Black, Player::White),
+            std::cmp::Ordering::Less => (Player::White, Player::Black),
+            std::cmp::Ordering::Equal => return None,
+        };
+        match self {
+            Goal::MostDiscs => Some(more),
+            Goal::FewestDiscs => Some(fewer),
+        }
+    }
+}
+
+// The name `parse` reads
+impl fmt::Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Goal::MostDiscs => write!(f, "standard"),
+            Goal::FewestDiscs => write!(f, "anti"),
+        }
+    }
+}
+
+// How the board is set up before the first move
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Setup {
+    #[default]
+    Standard,
+    // This many random legal moves from the standard start
+    Random { plies: usize },
+    // A position in the position notation
+    Position(String),
+}
+
+impl Setup {
+    // Read "standard", "random" or "random:N". Positions are given
+    // separately, as `Setup::Position`.
+    pub fn parse(text: &str) -> Result<Setup, RulesError> {
+        let bad_setup = || RulesError::BadSetup { text: text.to_string() };
+        match text.split_once(':') {
+            None if text == "standard" => Ok(Setup::Standard),
+            None if text == "random" => Ok(Setup::Random { plies: RANDOM_PLIES }),
+            Some(("random", plies)) => Ok(Setup::Random { plies: plies.parse().map_err(|_| bad_setup())? }),
+            _ => Err(bad_setup()),
+        }
+    }
+}
+
+// Why rules or a starting position could not be set up
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RulesError {
+    // Not "standard" or "anti"
+    BadGoal { text: String },
+    // Not "standard", "random" or "random:N"
+    BadSetup { text: String },
+    // The starting position could not be read
+    BadPosition(PositionError),
+    // A blocked square is not a square of the board
+    BadSquare(MoveError),
+    // A blocked square holds a disc at the start
+    Occupied { row: usize, col: usize },
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RulesError::BadGoal { text } => write!(f, "'{}' is not a variant: use standard or anti", text),
+            RulesError::BadSetup { text } => {
+                write!(f, "'{}' is not a start: use standard, random or random:<moves>", text)
+            }
+            RulesError::BadPosition(error) => write!(f, "{}", error),
+            RulesError::BadSquare(error) => write!(f, "bad blocked square: {}", error),
+            RulesError::Occupied { row, col } => {
+                write!(f, "{} cannot be blocked, it holds a disc at the start", format_move(*row, *col))
+            }
+        }
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+// Read a list of squares in RowCol form separated by commas or spaces, on a
+// board with `size` rows and columns
+pub fn parse_blocked(text: &str, size: usize) -> Result<Vec<(usize, usize)>, RulesError> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .map(|word| try_parse_input_sized(word, size).map_err(RulesError::BadSquare))
+        .collect()
+}
+
+// A full set of rules
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Rules {
+    pub goal: Goal,
+    pub setup: Setup,
+    pub blocked: Vec<(usize, usize)>,
+}
+
+impl Rules {
+    // Standard Othello
+    pub fn standard() -> Rules {
+        Rules::default()
+    }
+
+    // Whether the game is plain Othello apart from where it starts: the
+    // computer player only knows these rules
+    pub fn has_standard_play(&self) -> bool {
+        let position_blocks = matches!(&self.setup, Setup::Position(text) if text.contains('#'));
+        self.goal == Goal::MostDiscs && self.blocked.is_empty() && !position_blocks
+    }
+
+    // The starting position on an N x N board. `rng` is only used by the
+    // random start.
+    pub fn start<const N: usize>(&self, rng: &mut impl Rng) -> Result<Game<N>, RulesError> {
+        let game = match &self.setup {
+            Setup::Position(text) => {
+                let (board, player) = parse_position_sized(text).map_err(RulesError::BadPosition)?;
+                Game::from_board(&board, player)
+            }
+            Setup::Standard | Setup::Random { .. } => Game::new_sized(),
+        };
+
+        let mut grid = *game.grid();
+        for &(row, col) in &self.blocked {
+            if row >= N || col >= N {
+                return Err(RulesError::BadSquare(MoveError::OffBoard { row, col }));
+            }
+            if grid[row][col].player().is_some() {
+                return Err(RulesError::Occupied { row, col });
+            }
+            grid[row][col] = Cell::Blocked;
+        }
+        let mut game = Game::from_grid(grid, game.to_move()).with_goal(self.goal);
+
+        // Random moves come after the blocks so they respect them
+        if let Setup::Random { plies } = self.setup {
+            game.play_random(plies, rng);
+        }
+        Ok(game)
+    }
+}
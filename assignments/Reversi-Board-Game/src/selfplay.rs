@@ -0,0 +1,153 @@
+// Self-play games for training evaluations. Every position in which a move
+// was played is kept together with the final result of its game.
+//
+// Datasets are CSV files with a header line and one position per line:
+//
+//   black,white,to_move,result
+//   0000000810000000,0000001008000000,B,-6
+//
+// `black` and `white` are the bitboard masks in hex, and `result` is the
+// final disc count of Black minus White.
+
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::bitboard::Bitboard;
+use crate::engine::{Engine, Heuristic, Limit};
+use crate::game::{Game, Player};
+use crate::tournament::random_opening;
+
+pub const CSV_HEADER: &str = "black,white,to_move,result";
+
+// A position from a self-play game and how the game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    pub board: Bitboard,
+    pub to_move: Player,
+    // Final black discs minus white discs
+    pub result: i8,
+}
+
+impl Sample {
+    // The result from the side to move's point of view
+    pub fn mover_result(&self) -> i32 {
+        match self.to_move {
+            Player::Black => self.result as i32,
+            Player::White => -(self.result as i32),
+        }
+    }
+}
+
+// Settings for generating games
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    pub games: usize,
+    // Search depth of both engines
+    pub depth: u32,
+    // Chance of playing a random legal move instead of the engine's choice,
+    // from 0 to 1
+    pub randomness: f64,
+    // Random moves played before the engines take over; these positions
+    // are not recorded
+    pub opening_plies: usize,
+    pub seed: u64,
+}
+
+// Play one game from `start` and return its recorded positions
+fn play_game(engine: &mut Engine, start: Game, randomness: f64, rng: &mut StdRng) -> Vec<Sample> {
+    let mut game = start;
+    let mut positions = Vec::new();
+    while !game.is_over() {
+        if game.must_pass() {
+            game.pass();
+            continue;
+        }
+        positions.push((Bitboard::from_grid(game.grid()), game.to_move()));
+        if rng.gen_bool(randomness) {
+            game.play_random(1, rng);
+        } else {
+            let (row, col) = engine.choose_move(&game).expect("side to move has a legal move");
+            game.play(row, col);
+        }
+    }
+
+    let (black, white) = game.score();
+    let result = black as i8 - white as i8;
+    positions
+        .into_iter()
+        .map(|(board, to_move)| Sample { board, to_move, result })
+        .collect()
+}
+
+// Play `settings.games` games and collect their positions. `progress` is
+// called after each game with the number of games finished.
+pub fn generate(settings: Settings, mut progress: impl FnMut(usize)) -> Vec<Sample> {
+    let mut rng = StdRng::seed_from_u64(settings.seed);
+    let mut engine = Engine::new(Heuristic::default(), Limit::Depth(settings.depth));
+    let mut samples = Vec::new();
+    for played in 1..=settings.games {
+        let start = random_opening(&mut rng, settings.opening_plies);
+        samples.extend(play_game(&mut engine, start, settings.randomness, &mut rng));
+        progress(played);
+    }
+    samples
+}
+
+// Why a dataset could not be read. Line numbers start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatasetError {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for DatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl std::error::Error for DatasetError {}
+
+// Write samples in the CSV format, header included
+pub fn to_csv(samples: &[Sample]) -> String {
+    let mut text = String::from(CSV_HEADER);
+    text.push('\n');
+    for sample in samples {
+        text += &format!(
+            "{:016x},{:016x},{},{}\n",
+            sample.board.black, sample.board.white, sample.to_move, sample.result
+        );
+    }
+    text
+}
+
+// Read samples in the CSV format. The header line is optional.
+pub fn parse_csv(text: &str) -> Result<Vec<Sample>, DatasetError> {
+    let mut samples = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line == CSV_HEADER {
+            continue;
+        }
+        let error = |reason: &str| DatasetError { line: i + 1, reason: reason.to_string() };
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [black, white, to_move, result] = fields[..] else {
+            return Err(error("expected 4 fields"));
+        };
+        let black = u64::from_str_radix(black, 16).map_err(|_| error("bad black mask"))?;
+        let white = u64::from_str_radix(white, 16).map_err(|_| error("bad white mask"))?;
+        if black & white != 0 {
+            return Err(error("a square holds two discs"));
+        }
+        let mut side = to_move.chars();
+        let to_move = match (side.next().and_then(Player::from_char), side.next()) {
+            (Some(player), None) => player,
+            _ => return Err(error("side to move must be B or W")),
+        };
+        let result = result.parse().map_err(|_| error("bad result"))?;
+        samples.push(Sample { board: Bitboard { black, white, blocked: 0 }, to_move, result });
+    }
+    Ok(samples)
+}
@@ -0,0 +1,456 @@
+// A JSON game service over HTTP, for web front-ends and bots. Games live in
+// memory and are numbered from 1; any number of them can be played at once.
+//
+//   POST   /games             create a game; the optional body picks the
+//                             rules: {"variant": "anti", "start": "random",
+//                             "position": "...", "blocked": "aa,hh",
+//                             "seed": 7}
+//   GET    /games             ids of the games in progress
+//   GET    /games/<id>        the game state
+//   DELETE /games/<id>        forget a game
+//   GET    /games/<id>/moves  legal moves with the discs each flips
+//   POST   /games/<id>/moves  play {"move": "cd"} or {"move": "pass"}
+//   POST   /games/<id>/engine let the engine move; {"depth": 4} is optional
+//
+// Moves are written in RowCol form like the terminal game. Game states look
+// like:
+//
+//   {"id":1,"board":["........",...],"to_move":"B","black":2,"white":2,
+//    "must_pass":false,"over":false,"winner":null,"variant":"standard",
+//    "history":["cd","ce"]}
+//
+// Errors are {"error": reason} with a 4xx status. Every response closes the
+// connection.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde_json::{json, Value};
+
+use crate::engine::{Engine, Heuristic, Limit};
+use crate::history::{History, Move};
+use crate::rules::{parse_blocked, Goal, Rules, Setup};
+use crate::{format_move, try_parse_input, Game, SIZE};
+
+// Deepest search a client may ask the engine for
+pub const MAX_ENGINE_DEPTH: u32 = 10;
+
+// Longest request line or header line accepted, in bytes
+pub const MAX_LINE: usize = 8 * 1024;
+
+// Requests with more header lines or a bigger body are refused
+const MAX_HEADERS: usize = 100;
+const MAX_BODY: usize = 64 * 1024;
+
+// Connections answered at once; more are turned away until one finishes
+const MAX_CONNECTIONS: usize = 64;
+
+// Pause after a failed accept, so a persistent failure such as running out
+// of file descriptors does not spin
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+// A client that stalls this long mid-request is dropped
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+// An HTTP response: a status code and a JSON body, if any
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Option<Value>,
+}
+
+impl Response {
+    fn ok(body: Value) -> Response {
+        Response { status: 200, body: Some(body) }
+    }
+
+    fn error(status: u16, reason: impl ToString) -> Response {
+        Response { status, body: Some(json!({ "error": reason.to_string() })) }
+    }
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Error",
+    }
+}
+
+// One game and the rules it was created with
+struct Session {
+    rules: Rules,
+    history: History,
+}
+
+impl Session {
+    fn state(&self, id: u64) -> Value {
+        let game = self.history.game();
+        let (black, white) = game.score();
+        let rows: Vec<String> = game.board().iter().map(|row| row.iter().collect()).collect();
+        let history: Vec<String> = self.history.moves().iter().map(|&mv| move_name(mv)).collect();
+        json!({
+            "id": id,
+            "board": rows,
+            "to_move": game.to_move().to_string(),
+            "black": black,
+            "white": white,
+            "must_pass": game.must_pass(),
+            "over": game.is_over(),
+            "winner": if game.is_over() { game.winner().map(|player| player.to_string()) } else { None },
+            "variant": game.goal().to_string(),
+            "history": history,
+        })
+    }
+}
+
+fn move_name(mv: Move) -> String {
+    match mv {
+        Move::Play(row, col) => format_move(row, col),
+        Move::Pass => "pass".to_string(),
+    }
+}
+
+// The body as a JSON object; an empty body is an empty object
+fn parse_body(body: &str) -> Result<serde_json::Map<String, Value>, Response> {
+    if body.trim().is_empty() {
+        return Ok(serde_json::Map::new());
+    }
+    match serde_json::from_str(body) {
+        Ok(Value::Object(fields)) => Ok(fields),
+        Ok(_) => Err(Response::error(400, "the body must be a JSON object")),
+        Err(err) => Err(Response::error(400, format!("invalid JSON: {}", err))),
+    }
+}
+
+// A string field of the body, if present
+fn string_field<'a>(fields: &'a serde_json::Map<String, Value>, name: &str) -> Result<Option<&'a str>, Response> {
+    match fields.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(text)) => Ok(Some(text)),
+        Some(_) => Err(Response::error(400, format!("'{}' must be a string", name))),
+    }
+}
+
+// A non-negative integer field of the body, if present
+fn number_field(fields: &serde_json::Map<String, Value>, name: &str) -> Result<Option<u64>, Response> {
+    match fields.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => match value.as_u64() {
+            Some(number) => Ok(Some(number)),
+            None => Err(Response::error(400, format!("'{}' must be a non-negative integer", name))),
+        },
+    }
+}
+
+// The rules asked for when creating a game
+fn parse_rules(fields: &serde_json::Map<String, Value>) -> Result<Rules, Response> {
+    let bad_rules = |err: crate::rules::RulesError| Response::error(400, err);
+    let goal = match string_field(fields, "variant")? {
+        Some(text) => Goal::parse(text).map_err(bad_rules)?,
+        None => Goal::MostDiscs,
+    };
+    let setup = match (string_field(fields, "position")?, string_field(fields, "start")?) {
+        (Some(_), Some(_)) => return Err(Response::error(400, "give either 'position' or 'start', not both")),
+        (Some(position), None) => Setup::Position(position.to_string()),
+        (None, Some(start)) => Setup::parse(start).map_err(bad_rules)?,
+        (None, None) => Setup::Standard,
+    };
+    let blocked = match string_field(fields, "blocked")? {
+        Some(text) => parse_blocked(text, SIZE).map_err(bad_rules)?,
+        None => Vec::new(),
+    };
+    Ok(Rules { goal, setup, blocked })
+}
+
+// The game store and request handling, independent of HTTP
+pub struct Service {
+    // Each game has its own lock, so a slow engine move in one game does
+    // not hold up the others
+    games: Mutex<HashMap<u64, Arc<Mutex<Session>>>>,
+    next_id: Mutex<u64>,
+    // Engine depth when a request does not give one
+    depth: u32,
+}
+
+impl Service {
+    pub fn new(depth: u32) -> Service {
+        Service { games: Mutex::new(HashMap::new()), next_id: Mutex::new(1), depth: depth.clamp(1, MAX_ENGINE_DEPTH) }
+    }
+
+    fn session(&self, id: u64) -> Option<Arc<Mutex<Session>>> {
+        self.games.lock().unwrap().get(&id).cloned()
+    }
+
+    // Answer one request. `path` may carry a query string, which is ignored.
+    pub fn handle(&self, method: &str, path: &str, body: &str) -> Response {
+        let path = path.split('?').next().unwrap_or("");
+        let parts: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+        match parts[..] {
+            ["games"] => match method {
+                "GET" => self.list(),
+                "POST" => self.create(body),
+                _ => Response::error(405, format!("{} is not allowed on /games", method)),
+            },
+            ["games", id, ref rest @ ..] => {
+                let Some(session) = id.parse().ok().and_then(|id| self.session(id).map(|session| (id, session))) else {
+                    return Response::error(404, format!("no game {}", id));
+                };
+                let (id, session) = session;
+                match (method, rest) {
+                    ("GET", []) => Response::ok(session.lock().unwrap().state(id)),
+                    ("DELETE", []) => {
+                        self.games.lock().unwrap().remove(&id);
+                        Response { status: 204, body: None }
+                    }
+                    ("GET", ["moves"]) => legal_moves(&session.lock().unwrap()),
+                    ("POST", ["moves"]) => play(id, &mut session.lock().unwrap(), body),
+                    ("POST", ["engine"]) => self.engine_move(id, &mut session.lock().unwrap(), body),
+                    (_, [] | ["moves"] | ["engine"]) => {
+                        Response::error(405, format!("{} is not allowed on {}", method, path))
+                    }
+                    _ => Response::error(404, format!("no such endpoint {}", path)),
+                }
+            }
+            _ => Response::error(404, format!("no such endpoint {}", path)),
+        }
+    }
+
+    fn list(&self) -> Response {
+        let mut ids: Vec<u64> = self.games.lock().unwrap().keys().copied().collect();
+        ids.sort_unstable();
+        Response::ok(json!({ "games": ids }))
+    }
+
+    fn create(&self, body: &str) -> Response {
+        let result = parse_body(body).and_then(|fields| {
+            let rules = parse_rules(&fields)?;
+            let seed = number_field(&fields, "seed")?.unwrap_or_else(rand::random);
+            let start: Game = rules.start(&mut StdRng::seed_from_u64(seed)).map_err(|err| Response::error(400, err))?;
+            Ok(Session { rules, history: History::from_game(start) })
+        });
+        let session = match result {
+            Ok(session) => session,
+            Err(response) => return response,
+        };
+
+        let id = {
+            let mut next_id = self.next_id.lock().unwrap();
+            *next_id += 1;
+            *next_id - 1
+        };
+        let state = session.state(id);
+        self.games.lock().unwrap().insert(id, Arc::new(Mutex::new(session)));
+        Response { status: 201, body: Some(state) }
+    }
+
+    fn engine_move(&self, id: u64, session: &mut Session, body: &str) -> Response {
+        let depth = match parse_body(body).and_then(|fields| number_field(&fields, "depth")) {
+            Ok(Some(depth)) if (1..=MAX_ENGINE_DEPTH as u64).contains(&depth) => depth as u32,
+            Ok(Some(_)) => return Response::error(400, format!("'depth' must be from 1 to {}", MAX_ENGINE_DEPTH)),
+            Ok(None) => self.depth,
+            Err(response) => return response,
+        };
+        if !session.rules.has_standard_play() {
+            return Response::error(409, "the engine only plays the standard variant without blocked squares");
+        }
+        let game = session.history.game();
+        if game.is_over() {
+            return Response::error(409, "the game is over");
+        }
+
+        let mv = match Engine::new(Heuristic::default(), Limit::Depth(depth)).choose_move(game) {
+            Some((row, col)) => Move::Play(row, col),
+            None => Move::Pass,
+        };
+        session.history.apply(mv);
+        let mut state = session.state(id);
+        state["move"] = json!(move_name(mv));
+        Response::ok(state)
+    }
+}
+
+fn legal_moves(session: &Session) -> Response {
+    let game = session.history.game();
+    let moves: Vec<Value> = game
+        .list_moves()
+        .iter()
+        .map(|mv| json!({ "move": format_move(mv.row, mv.col), "flips": mv.flips }))
+        .collect();
+    Response::ok(json!({
+        "to_move": game.to_move().to_string(),
+        "must_pass": game.must_pass(),
+        "moves": moves,
+    }))
+}
+
+fn play(id: u64, session: &mut Session, body: &str) -> Response {
+    let fields = match parse_body(body) {
+        Ok(fields) => fields,
+        Err(response) => return response,
+    };
+    let text = match string_field(&fields, "move") {
+        Ok(Some(text)) => text.trim(),
+        Ok(None) => return Response::error(400, "missing 'move'"),
+        Err(response) => return response,
+    };
+    let player = session.history.game().to_move();
+    if session.history.game().is_over() {
+        return Response::error(409, "the game is over");
+    }
+
+    if text.eq_ignore_ascii_case("pass") {
+        if !session.history.pass() {
+            return Response::error(409, format!("{} has a legal move and cannot pass", player.name()));
+        }
+    } else {
+        let (row, col) = match try_parse_input(text) {
+            Ok(square) => square,
+            Err(err) => return Response::error(400, err),
+        };
+        if let Err(err) = session.history.try_play(row, col) {
+            return Response::error(409, err);
+        }
+    }
+    Response::ok(session.state(id))
+}
+
+// A parsed HTTP request
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+// Read one request. Returns Ok(None) if the connection closed first, and an
+// error response for requests that cannot be handled.
+fn read_request(reader: &mut impl BufRead) -> io::Result<Result<Option<Request>, Response>> {
+    let mut line = String::new();
+    match read_line(reader, &mut line)? {
+        None => return Ok(Err(Response::error(431, "the request line is too long"))),
+        Some(0) => return Ok(Ok(None)),
+        Some(_) => {}
+    }
+    let mut words = line.split_whitespace();
+    let (Some(method), Some(path)) = (words.next(), words.next()) else {
+        return Ok(Err(Response::error(400, "malformed request line")));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut length = 0;
+    for _ in 0..MAX_HEADERS {
+        line.clear();
+        let Some(read) = read_line(reader, &mut line)? else {
+            return Ok(Err(Response::error(431, "a header line is too long")));
+        };
+        if read == 0 || line.trim().is_empty() {
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body)?;
+            let Ok(body) = String::from_utf8(body) else {
+                return Ok(Err(Response::error(400, "the body is not UTF-8")));
+            };
+            return Ok(Ok(Some(Request { method, path, body })));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = match value.trim().parse() {
+                    Ok(length) if length <= MAX_BODY => length,
+                    Ok(_) => return Ok(Err(Response::error(413, "the body is too large"))),
+                    Err(_) => return Ok(Err(Response::error(400, "bad Content-Length"))),
+                };
+            }
+        }
+    }
+    Ok(Err(Response::error(413, "too many headers")))
+}
+
+// Read one line of at most MAX_LINE bytes, returning the bytes read or None
+// if the line is longer
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> io::Result<Option<usize>> {
+    let read = reader.take(MAX_LINE as u64).read_line(line)?;
+    Ok(if read == MAX_LINE && !line.ends_with('\n') { None } else { Some(read) })
+}
+
+fn write_response(stream: &mut impl Write, response: &Response) -> io::Result<()> {
+    let body = response.body.as_ref().map_or(String::new(), |body| body.to_string());
+    write!(stream, "HTTP/1.1 {} {}\r\n", response.status, reason_phrase(response.status))?;
+    if response.body.is_some() {
+        write!(stream, "Content-Type: application/json\r\n")?;
+    }
+    write!(stream, "Content-Length: {}\r\n", body.len())?;
+    write!(stream, "Access-Control-Allow-Origin: *\r\n")?;
+    write!(stream, "Access-Control-Allow-Methods: GET, POST, DELETE, OPTIONS\r\n")?;
+    write!(stream, "Access-Control-Allow-Headers: Content-Type\r\n")?;
+    write!(stream, "Connection: close\r\n\r\n{}", body)?;
+    stream.flush()
+}
+
+// Answer the one request on a connection
+fn handle_connection(service: &Service, stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let response = match read_request(&mut reader)? {
+        Ok(None) => return Ok(()),
+        // Browsers check cross-origin requests first; the headers on every
+        // response allow them
+        Ok(Some(request)) if request.method == "OPTIONS" => Response { status: 204, body: None },
+        Ok(Some(request)) => service.handle(&request.method, &request.path, &request.body),
+        Err(response) => response,
+    };
+    let mut stream = stream;
+    write_response(&mut stream, &response)
+}
+
+// Counts a connection as open until it is dropped, however its thread ends
+struct Slot(Arc<AtomicUsize>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// Serve requests on the listener, each connection on its own thread, up to
+// MAX_CONNECTIONS at once; further clients get a 503. A failed accept is
+// logged and retried after a pause, as it usually concerns only the client
+// that was connecting.
+pub fn serve(listener: &TcpListener, service: Arc<Service>) {
+    let open = Arc::new(AtomicUsize::new(0));
+    loop {
+        let mut stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                eprintln!("Failed to accept a connection: {}", err);
+                thread::sleep(ACCEPT_BACKOFF);
+                continue;
+            }
+        };
+        if open.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            open.fetch_sub(1, Ordering::SeqCst);
+            let _ = write_response(&mut stream, &Response::error(503, "too many connections"));
+            continue;
+        }
+        let slot = Slot(Arc::clone(&open));
+        let service = Arc::clone(&service);
+        thread::spawn(move || {
+            let _slot = slot;
+            // A client that goes away mid-request only loses its own answer
+            let _ = handle_connection(&service, stream);
+        });
+    }
+}
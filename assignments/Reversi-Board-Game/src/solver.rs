@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use crate::bitboard::{self, squares, Bitboard};
+use crate::game::Player;
+
+// Only positions with at least this many empty squares go in the table; the
+// last few plies are cheaper to search again than to look up
+const TABLE_MIN_EMPTIES: u32 = 6;
+
+// Below this many empty squares moves are searched in plain order instead
+// of sorting them by the opponent's mobility
+const SORT_MIN_EMPTIES: u32 = 7;
+
+// The exact result of a position under perfect play
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Solution {
+    // Final disc count of the side to move minus that of its opponent
+    pub score: i32,
+    // Square index of a best move, or None if the side to move must pass
+    // or the game is over
+    pub best_move: Option<usize>,
+    // Positions visited
+    pub nodes: u64,
+}
+
+// What is known about a position: its score lies in lower..=upper
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    lower: i8,
+    upper: i8,
+    best_move: u8,
+}
+
+// Exact endgame search with a transposition table. The table is kept
+// between calls so related positions can be solved faster.
+#[derive(Debug, Default)]
+pub struct Solver {
+    // Keyed by (discs of side to move, discs of opponent)
+    table: HashMap<(u64, u64), Entry>,
+    nodes: u64,
+    // Blocked squares of the positions in the table
+    blocked: u64,
+}
+
+impl Solver {
+    pub fn new() -> Solver {
+        Solver::default()
+    }
+
+    // Forget all stored positions
+    pub fn clear(&mut self) {
+        self.table.clear();
+    }
+
+    // Number of stored positions
+    pub fn table_len(&self) -> usize {
+        self.table.len()
+    }
+
+    // Solve the position for `player`. The cost grows exponentially with the
+    // number of empty squares, so this is meant for the last 20 or so moves.
+    pub fn solve(&mut self, board: &Bitboard, player: Player) -> Solution {
+        self.nodes = 0;
+        if board.blocked != self.blocked {
+            self.table.clear();
+            self.blocked = board.blocked;
+        }
+        let own = board.discs(player);
+        let opp = board.discs(player.opponent());
+
+        let moves = self.moves(own, opp);
+        if moves == 0 {
+            let score = self.negamax(own, opp, -64, 64);
+            return Solution { score, best_move: None, nodes: self.nodes };
+        }
+
+        let mut alpha = -65;
+        let mut best_move = None;
+        for sq in self.ordered_moves(own, opp, moves, None) {
+            let flipped = bitboard::flips(own, opp, sq);
+            let score = -self.negamax(opp & !flipped, own | flipped | (1 << sq), -64, -alpha);
+            if score > alpha {
+                alpha = score;
+                best_move = Some(sq);
+            }
+        }
+        Solution { score: alpha, best_move, nodes: self.nodes }
+    }
+
+    fn negamax(&mut self, own: u64, opp: u64, mut alpha: i32, mut beta: i32) -> i32 {
+        self.nodes += 1;
+
+        let moves = self.moves(own, opp);
+        if moves == 0 {
+            if self.moves(opp, own) == 0 {
+                return own.count_ones() as i32 - opp.count_ones() as i32;
+            }
+            return -self.negamax(opp, own, -beta, -alpha);
+        }
+
+        let empties = (!(own | opp | self.blocked)).count_ones();
+        let use_table = empties >= TABLE_MIN_EMPTIES;
+        let mut hint = None;
+        if use_table {
+            if let Some(entry) = self.table.get(&(own, opp)) {
+                let (lower, upper) = (entry.lower as i32, entry.upper as i32);
+                if lower >= beta {
+                    return lower;
+                }
+                if upper <= alpha || lower == upper {
+                    return upper;
+                }
+                alpha = alpha.max(lower);
+                beta = beta.min(upper);
+                hint = Some(entry.best_move as usize);
+            }
+        }
+
+        let window_alpha = alpha;
+        let mut best = -65;
+        let mut best_move = 0;
+        for sq in self.ordered_moves(own, opp, moves, hint) {
+            let flipped = bitboard::flips(own, opp, sq);
+            let score = -self.negamax(opp & !flipped, own | flipped | (1 << sq), -beta, -alpha);
+            if score > best {
+                best = score;
+                best_move = sq;
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if use_table {
+            // A fail-low only gives an upper bound and a fail-high a lower one
+            let lower = if best > window_alpha { best } else { -64 };
+            let upper = if best < beta { best } else { 64 };
+            let entry = self.table.entry((own, opp)).or_insert(Entry { lower: -64, upper: 64, best_move: 0 });
+            entry.lower = entry.lower.max(lower as i8);
+            entry.upper = entry.upper.min(upper as i8);
+            entry.best_move = best_move as u8;
+        }
+        best
+    }
+
+    // Legal moves of the owner of `own`, leaving out blocked squares
+    #[inline]
+    fn moves(&self, own: u64, opp: u64) -> u64 {
+        bitboard::moves(own, opp) & !self.blocked
+    }
+
+    // The hinted move first, then moves that leave the opponent the fewest
+    // replies, which tends to produce cutoffs early
+    fn ordered_moves(&self, own: u64, opp: u64, moves: u64, hint: Option<usize>) -> Vec<usize> {
+        let mut ordered: Vec<usize> = squares(moves).collect();
+        if (!(own | opp | self.blocked)).count_ones() >= SORT_MIN_EMPTIES {
+            ordered.sort_by_key(|&sq| {
+                let flipped = bitboard::flips(own, opp, sq);
+                self.moves(opp & !flipped, own | flipped | (1 << sq)).count_ones()
+            });
+        }
+        if let Some(hint) = hint {
+            if let Some(i) = ordered.iter().position(|&sq| sq == hint) {
+                ordered[..=i].rotate_right(1);
+            }
+        }
+        ordered
+    }
+}
+
+// Solve a position with a fresh solver
+pub fn solve(board: &Bitboard, player: Player) -> Solution {
+    Solver::new().solve(board, player)
+}
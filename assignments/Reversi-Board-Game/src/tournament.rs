@@ -0,0 +1,171 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::agent::Agent;
+use crate::game::{Game, Player};
+
+// Play a game to the end from `start`. Returns the final black and white
+// disc counts.
+pub fn play_game(black: &mut dyn Agent, white: &mut dyn Agent, start: Game) -> (usize, usize) {
+    let mut game = start;
+    while !game.is_over() {
+        if game.must_pass() {
+            game.pass();
+            continue;
+        }
+        let agent: &mut dyn Agent = match game.to_move() {
+            Player::Black => &mut *black,
+            Player::White => &mut *white,
+        };
+        let chosen = agent.choose_move(&game);
+        match chosen {
+            Some((row, col)) if game.play(row, col) => {}
+            _ => panic!("{} chose an illegal move {:?}", agent.name(), chosen),
+        }
+    }
+    game.score()
+}
+
+// A position reached by `plies` random legal moves from the start. Stops
+// early if the game ends.
+pub fn random_opening(rng: &mut StdRng, plies: usize) -> Game {
+    let mut game = Game::new();
+    game.play_random(plies, rng);
+    game
+}
+
+// Wins, draws and losses from one side's point of view
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Record {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+impl Record {
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    // Points per game, counting a draw as half a win
+    pub fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games() as f64
+    }
+
+    // The same games seen from the other side
+    pub fn reversed(&self) -> Record {
+        Record { wins: self.losses, draws: self.draws, losses: self.wins }
+    }
+
+    fn add(&mut self, own: usize, opp: usize) {
+        match own.cmp(&opp) {
+            std::cmp::Ordering::Greater => self.wins += 1,
+            std::cmp::Ordering::Equal => self.draws += 1,
+            std::cmp::Ordering::Less => self.losses += 1,
+        }
+    }
+}
+
+// Elo difference implied by an expected score. Perfect scores are clamped
+// so the result stays finite.
+pub fn elo_difference(score: f64) -> f64 {
+    let score = score.clamp(0.001, 0.999);
+    400.0 * (score / (1.0 - score)).log10()
+}
+
+// Expected score of a player rated `diff` points above its opponent
+pub fn expected_score(diff: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-diff / 400.0))
+}
+
+// Settings for a round robin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Settings {
+    // Games per pairing; each opening is played twice with colours swapped,
+    // so odd counts are rounded up
+    pub games: usize,
+    // Random moves played before the agents take over
+    pub opening_plies: usize,
+    pub seed: u64,
+}
+
+// Results of a round robin. `records[i][j]` is player i's record against j.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standings {
+    pub names: Vec<String>,
+    pub records: Vec<Vec<Record>>,
+}
+
+impl Standings {
+    // Player i's record against everyone
+    pub fn total(&self, i: usize) -> Record {
+        self.records[i].iter().fold(Record::default(), |acc, r| Record {
+            wins: acc.wins + r.wins,
+            draws: acc.draws + r.draws,
+            losses: acc.losses + r.losses,
+        })
+    }
+
+    // Elo ratings fitted to all results, averaging 0. Each pairing gets one
+    // extra virtual draw so a clean sweep does not push ratings to infinity.
+    pub fn elo_ratings(&self) -> Vec<f64> {
+        let n = self.names.len();
+        let mut ratings = vec![0.0; n];
+        for _ in 0..1000 {
+            for i in 0..n {
+                let (mut actual, mut expected, mut games) = (0.0, 0.0, 0.0);
+                for j in (0..n).filter(|&j| j != i) {
+                    let record = self.records[i][j];
+                    let played = record.games() as f64 + 1.0;
+                    actual += record.wins as f64 + 0.5 * record.draws as f64 + 0.5;
+                    expected += played * expected_score(ratings[i] - ratings[j]);
+                    games += played;
+                }
+                if games > 0.0 {
+                    ratings[i] += 400.0 * (actual - expected) / games;
+                }
+            }
+            let mean = ratings.iter().sum::<f64>() / n as f64;
+            ratings.iter_mut().for_each(|r| *r -= mean);
+        }
+        ratings
+    }
+}
+
+// Play every agent against every other. `progress` is called after each
+// game with the indices of the black and white agents and the final score.
+pub fn round_robin(
+    agents: &mut [Box<dyn Agent>],
+    settings: Settings,
+    mut progress: impl FnMut(usize, usize, (usize, usize)),
+) -> Standings {
+    let n = agents.len();
+    let mut rng = StdRng::seed_from_u64(settings.seed);
+    let mut records = vec![vec![Record::default(); n]; n];
+
+    for i in 0..n {
+        for j in i + 1..n {
+            for _ in 0..settings.games.div_ceil(2) {
+                let opening = random_opening(&mut rng, settings.opening_plies);
+                for (black, white) in [(i, j), (j, i)] {
+                    // Borrow both agents at once; i < j always holds
+                    let (head, tail) = agents.split_at_mut(j);
+                    let (first, second) = (head[i].as_mut(), tail[0].as_mut());
+                    let (b, w) = if black == i {
+                        play_game(first, second, opening.clone())
+                    } else {
+                        play_game(second, first, opening.clone())
+                    };
+                    records[black][white].add(b, w);
+                    records[white][black].add(w, b);
+                    progress(black, white, (b, w));
+                }
+            }
+        }
+    }
+
+    Standings { names: agents.iter().map(|agent| agent.name()).collect(), records }
+}
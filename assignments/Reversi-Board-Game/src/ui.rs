@@ -0,0 +1,97 @@
+// Terminal-independent state for the full-screen interface: the cursor that
+// picks a square, and which discs to animate after a move. Drawing lives in
+// the binary so the library does not depend on a terminal crate.
+
+use crate::game::Game;
+use crate::history::Move;
+use crate::SIZE;
+
+// A step of the cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+// The highlighted square on an N x N board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor<const N: usize = SIZE> {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl<const N: usize> Default for Cursor<N> {
+    fn default() -> Self {
+        Cursor::new(0, 0)
+    }
+}
+
+impl<const N: usize> Cursor<N> {
+    // A cursor on (row, col), moved onto the board if it is off it
+    pub fn new(row: usize, col: usize) -> Cursor<N> {
+        Cursor { row: row.min(N - 1), col: col.min(N - 1) }
+    }
+
+    // Move one square, stopping at the edge of the board
+    pub fn step(&mut self, direction: Direction) {
+        match direction {
+            Direction::Up => self.row = self.row.saturating_sub(1),
+            Direction::Down => self.row = (self.row + 1).min(N - 1),
+            Direction::Left => self.col = self.col.saturating_sub(1),
+            Direction::Right => self.col = (self.col + 1).min(N - 1),
+        }
+    }
+
+    // Jump to the next legal move after the cursor in row-major order,
+    // wrapping around. Stays put if there is no legal move.
+    pub fn next_legal(&mut self, game: &Game<N>) {
+        let here = self.row * N + self.col;
+        let moves = game.legal_moves();
+        let next = moves
+            .iter()
+            .find(|&&(row, col)| row * N + col > here)
+            .or_else(|| moves.first());
+        if let Some(&(row, col)) = next {
+            *self = Cursor { row, col };
+        }
+    }
+
+    // Jump to the previous legal move, wrapping around
+    pub fn prev_legal(&mut self, game: &Game<N>) {
+        let here = self.row * N + self.col;
+        let moves = game.legal_moves();
+        let prev = moves
+            .iter()
+            .rev()
+            .find(|&&(row, col)| row * N + col < here)
+            .or_else(|| moves.last());
+        if let Some(&(row, col)) = prev {
+            *self = Cursor { row, col };
+        }
+    }
+}
+
+// Squares holding a disc in both positions whose colour changed, i.e. the
+// discs flipped by the move between them, in row-major order
+pub fn flipped_squares<const N: usize>(before: &Game<N>, after: &Game<N>) -> Vec<(usize, usize)> {
+    let mut flipped = Vec::new();
+    for row in 0..N {
+        for col in 0..N {
+            let (old, new) = (before.cell(row, col).player(), after.cell(row, col).player());
+            if old.is_some() && new.is_some() && old != new {
+                flipped.push((row, col));
+            }
+        }
+    }
+    flipped
+}
+
+// The square of the most recent disc placed, skipping passes
+pub fn last_play(moves: &[Move]) -> Option<(usize, usize)> {
+    moves.iter().rev().find_map(|mv| match *mv {
+        Move::Play(row, col) => Some((row, col)),
+        Move::Pass => None,
+    })
+}
@@ -0,0 +1,157 @@
+// Zobrist hashing: every (colour, square) pair gets a fixed random 64-bit
+// key, and so does every blocked square. A position hashes to the XOR of
+// the keys of its discs and blocked squares, plus `SIDE_KEY` when White is
+// to move. Blocked squares never change, so they do not enter the update. A move changes only a few squares, so
+// the hash can be updated by XORing their keys in and out instead of being
+// recomputed.
+//
+// Keys are generated at compile time from a fixed seed, so hashes are the
+// same in every run and can be stored in files.
+
+use crate::bitboard::{squares, Bitboard, SYMMETRIES};
+use crate::game::{Cell, Grid, Player};
+use crate::Board;
+
+// Enough keys for the largest supported board
+const MAX_SQUARES: usize = 26 * 26;
+
+// SplitMix64: a simple generator with well spread output
+const fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+const fn build_keys() -> [[u64; MAX_SQUARES]; 2] {
+    let mut keys = [[0; MAX_SQUARES]; 2];
+    let mut i = 0;
+    while i < MAX_SQUARES {
+        keys[0][i] = splitmix64(2 * i as u64 + 1);
+        keys[1][i] = splitmix64(2 * i as u64 + 2);
+        i += 1;
+    }
+    keys
+}
+
+// Blocked square keys come from seeds past the disc keys, so positions
+// without blocked squares hash as they always have
+const fn build_blocked_keys() -> [u64; MAX_SQUARES] {
+    let mut keys = [0; MAX_SQUARES];
+    let mut i = 0;
+    while i < MAX_SQUARES {
+        keys[i] = splitmix64((2 * MAX_SQUARES + i) as u64 + 1);
+        i += 1;
+    }
+    keys
+}
+
+static KEYS: [[u64; MAX_SQUARES]; 2] = build_keys();
+static BLOCKED_KEYS: [u64; MAX_SQUARES] = build_blocked_keys();
+
+// XORed into the hash when White is to move
+pub const SIDE_KEY: u64 = splitmix64(0);
+
+// Key of a disc on square `index` (row * N + col)
+#[inline]
+pub fn square_key(player: Player, index: usize) -> u64 {
+    match player {
+        Player::Black => KEYS[0][index],
+        Player::White => KEYS[1][index],
+    }
+}
+
+// Key of a blocked square `index`
+#[inline]
+pub fn blocked_key(index: usize) -> u64 {
+    BLOCKED_KEYS[index]
+}
+
+// Key of a cell on square `index`; empty squares contribute nothing
+#[inline]
+pub fn cell_key(cell: Cell, index: usize) -> u64 {
+    match cell {
+        Cell::Blocked => blocked_key(index),
+        cell => cell.player().map_or(0, |player| square_key(player, index)),
+    }
+}
+
+fn side_key(to_move: Player) -> u64 {
+    match to_move {
+        Player::Black => 0,
+        Player::White => SIDE_KEY,
+    }
+}
+
+// Hash a typed grid from scratch
+pub fn hash_grid<const N: usize>(grid: &Grid<N>, to_move: Player) -> u64 {
+    let mut hash = side_key(to_move);
+    for (r, row) in grid.iter().enumerate() {
+        for (c, &cell) in row.iter().enumerate() {
+            hash ^= cell_key(cell, r * N + c);
+        }
+    }
+    hash
+}
+
+// Hash a character board from scratch. '#' is a blocked square; characters
+// other than 'B', 'W' and '#' count as empty squares.
+pub fn hash_board<const N: usize>(board: &Board<N>, to_move: Player) -> u64 {
+    hash_grid(&crate::game::grid_from_board(board), to_move)
+}
+
+// Hash a bitboard from scratch
+pub fn hash_bitboard(board: &Bitboard, to_move: Player) -> u64 {
+    let black = squares(board.black).fold(0, |hash, sq| hash ^ KEYS[0][sq]);
+    let white = squares(board.white).fold(0, |hash, sq| hash ^ KEYS[1][sq]);
+    let blocked = squares(board.blocked).fold(0, |hash, sq| hash ^ BLOCKED_KEYS[sq]);
+    black ^ white ^ blocked ^ side_key(to_move)
+}
+
+// The hash after `player` places a disc on `square` of a bitboard and flips
+// `flipped` (as returned by `Bitboard::apply`); the turn passes to the
+// opponent
+pub fn update(hash: u64, player: Player, square: usize, flipped: u64) -> u64 {
+    let opponent = player.opponent();
+    let flips = squares(flipped).fold(0, |acc, sq| acc ^ square_key(player, sq) ^ square_key(opponent, sq));
+    hash ^ square_key(player, square) ^ flips ^ SIDE_KEY
+}
+
+// Map (row, col) on an N x N board through symmetry 0..8, numbered as in
+// `bitboard::transform_square`
+pub fn transform_coords<const N: usize>(row: usize, col: usize, symmetry: usize) -> (usize, usize) {
+    let (mut row, mut col) = (row, col);
+    if symmetry & 1 != 0 {
+        (row, col) = (col, row);
+    }
+    if symmetry & 2 != 0 {
+        row = N - 1 - row;
+    }
+    if symmetry & 4 != 0 {
+        col = N - 1 - col;
+    }
+    (row, col)
+}
+
+// The grid seen through a symmetry of the board
+pub fn transform_grid<const N: usize>(grid: &Grid<N>, symmetry: usize) -> Grid<N> {
+    let mut image = [[Cell::Empty; N]; N];
+    for (r, row) in grid.iter().enumerate() {
+        for (c, &cell) in row.iter().enumerate() {
+            let (tr, tc) = transform_coords::<N>(r, c, symmetry);
+            image[tr][tc] = cell;
+        }
+    }
+    image
+}
+
+// The hash of the position's canonical copy, so rotated and mirrored copies
+// share one key. The canonical copy is the one with the smallest hash with
+// Black to move; `Bitboard::canonical` picks the same copy.
+pub fn canonical_hash<const N: usize>(grid: &Grid<N>, to_move: Player) -> u64 {
+    let smallest = (0..SYMMETRIES)
+        .map(|symmetry| hash_grid(&transform_grid(grid, symmetry), Player::Black))
+        .min()
+        .expect("there is always a symmetry");
+    smallest ^ side_key(to_move)
+}
@@ -0,0 +1,76 @@
+// Import serde_json to read the output of the batch subcommand
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use serde_json::Value;
+
+
+// Run `batch` with the moves on stdin. Returns the JSON records and whether
+// the process succeeded.
+fn run_batch(moves: &str) -> (Vec<Value>, bool) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_Reversi-Board-Game"))
+        .arg("batch")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(moves.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    let records = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    (records, output.status.success())
+}
+
+// Test a short game: one record per position and the result last
+#[test]
+fn test_batch_positions() {
+    let (records, ok) = run_batch("# opening\ncd, ce\n");
+    assert!(ok);
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[0]["ply"], 0);
+    assert_eq!(records[0]["to_move"], "B");
+    assert_eq!(records[1]["move"], "cd");
+    assert_eq!(records[1]["player"], "B");
+    assert_eq!(records[1]["board"][2], "...B....");
+    assert_eq!((records[1]["black"].clone(), records[1]["white"].clone()), (4.into(), 1.into()));
+    assert_eq!(records[2]["player"], "W");
+    assert_eq!(records[3]["result"]["black"], 3);
+    assert_eq!(records[3]["result"]["white"], 3);
+    assert_eq!(records[3]["result"]["winner"], Value::Null);
+    assert_eq!(records[3]["result"]["finished"], false);
+}
+
+// Test that the first illegal move stops the run with a nonzero status
+#[test]
+fn test_batch_illegal_move() {
+    let (records, ok) = run_batch("cd aa ce");
+    assert!(!ok);
+    let last = records.last().unwrap();
+    assert_eq!(last["error"], "illegal move");
+    assert_eq!(last["move"], "aa");
+    assert_eq!(last["ply"], 2);
+    assert_eq!(last["reason"], "aa does not flip any discs");
+
+    let (records, ok) = run_batch("zz");
+    assert!(!ok);
+    assert_eq!(records.last().unwrap()["error"], "invalid input");
+    assert_eq!(records.last().unwrap()["reason"], "zz is off the board");
+
+    let (records, ok) = run_batch("cd dd");
+    assert!(!ok);
+    assert_eq!(records.last().unwrap()["reason"], "dd is already taken");
+}
+
+// Test the shortest finished game, where White is wiped out in nine moves
+#[test]
+fn test_batch_finished_game() {
+    let (records, ok) = run_batch("cd cc cb bd ae fd gd ce df");
+    assert!(ok);
+    let result = &records.last().unwrap()["result"];
+    assert_eq!(result["finished"], true);
+    assert_eq!(result["white"], 0);
+    assert_eq!(result["winner"], "B");
+}
@@ -0,0 +1,117 @@
+// Import the bitboard move generator and the board functions it must agree with
+use proptest::prelude::*;
+use reversi::bitboard::{self, square, squares};
+use reversi::{apply_move, create_initial_board, has_valid_moves, is_valid_move, Bitboard, Board, Player, SIZE};
+
+
+// Build a board from 64 cells in row-major order
+fn board_from_cells(cells: &[char]) -> Board {
+    let mut board = [['.'; SIZE]; SIZE];
+    for (i, &cell) in cells.iter().enumerate() {
+        board[i / SIZE][i % SIZE] = cell;
+    }
+    board
+}
+
+// Arbitrary boards, not necessarily reachable from the initial position
+fn any_board() -> impl Strategy<Value = Board> {
+    prop::collection::vec(prop_oneof![Just('.'), Just('B'), Just('W')], SIZE * SIZE)
+        .prop_map(|cells| board_from_cells(&cells))
+}
+
+// Test conversion of the initial position to and from the array board
+#[test]
+fn test_initial_bitboard() {
+    let bitboard = Bitboard::initial();
+    assert_eq!(bitboard.to_board(), create_initial_board());
+    assert_eq!(bitboard.count(), (2, 2));
+
+    // Black's four opening moves: c4, d3, e6, f5
+    let moves: Vec<usize> = squares(bitboard.legal_moves(Player::Black)).collect();
+    assert_eq!(moves, vec![square(2, 3), square(3, 2), square(4, 5), square(5, 4)]);
+}
+
+// Test that moves never wrap around the edge of the board
+#[test]
+fn test_no_wraparound() {
+    let mut board = [['.'; SIZE]; SIZE];
+    board[0][7] = 'B'; // ah
+    board[1][0] = 'W'; // ba, adjacent to ah only in bit order
+    let bitboard = Bitboard::from_board(&board);
+    assert_eq!(bitboard.legal_moves(Player::Black), 0);
+    assert_eq!(bitboard.flips(Player::Black, square(1, 1)), 0);
+}
+
+proptest! {
+    // Conversion to and from the array board is lossless
+    #[test]
+    fn prop_round_trip(board in any_board()) {
+        prop_assert_eq!(Bitboard::from_board(&board).to_board(), board);
+    }
+
+    // The legal move mask matches is_valid_move on every square
+    #[test]
+    fn prop_legal_moves_match(board in any_board()) {
+        let bitboard = Bitboard::from_board(&board);
+        for player in [Player::Black, Player::White] {
+            let mask = bitboard.legal_moves(player);
+            for row in 0..SIZE {
+                for col in 0..SIZE {
+                    let legal = mask & (1 << square(row, col)) != 0;
+                    prop_assert_eq!(legal, is_valid_move(&board, player.to_char(), row, col));
+                }
+            }
+            prop_assert_eq!(mask != 0, has_valid_moves(&board, player.to_char()));
+        }
+    }
+
+    // Applying a move flips exactly the discs apply_move flips
+    #[test]
+    fn prop_apply_matches(board in any_board(), row in 0..SIZE, col in 0..SIZE) {
+        for player in [Player::Black, Player::White] {
+            let mut expected = board;
+            apply_move(&mut expected, player.to_char(), row, col);
+            let mut bitboard = Bitboard::from_board(&board);
+            bitboard.apply(player, square(row, col));
+            prop_assert_eq!(bitboard.to_board(), expected);
+        }
+    }
+
+    // Games of random legal moves stay in agreement with the array board
+    #[test]
+    fn prop_random_games_match(choices in prop::collection::vec(any::<usize>(), 60)) {
+        let mut board = create_initial_board();
+        let mut bitboard = Bitboard::initial();
+        let mut player = Player::Black;
+        for choice in choices {
+            let mut moves = bitboard.legal_moves(player);
+            if moves == 0 {
+                player = player.opponent();
+                moves = bitboard.legal_moves(player);
+                if moves == 0 {
+                    break;
+                }
+            }
+            let sq = squares(moves).nth(choice % moves.count_ones() as usize).unwrap();
+            let (row, col) = bitboard::coords(sq);
+            prop_assert!(is_valid_move(&board, player.to_char(), row, col));
+            apply_move(&mut board, player.to_char(), row, col);
+            bitboard.apply(player, sq);
+            prop_assert_eq!(bitboard.to_board(), board);
+            player = player.opponent();
+        }
+    }
+    // Symmetries are undone by their inverses, and legal moves follow the
+    // board through every symmetry
+    #[test]
+    fn prop_symmetries(board in any_board(), symmetry in 0..bitboard::SYMMETRIES) {
+        let bitboard = Bitboard::from_board(&board);
+        let image = bitboard.transform(symmetry);
+        prop_assert_eq!(image.transform(bitboard::inverse_symmetry(symmetry)), bitboard);
+        prop_assert_eq!(image.count(), bitboard.count());
+        for player in [Player::Black, Player::White] {
+            let moves = bitboard::transform(bitboard.legal_moves(player), symmetry);
+            prop_assert_eq!(image.legal_moves(player), moves);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// Import the opening book and the engine that consults it
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use reversi::bitboard::square;
+use reversi::history::parse_square_name;
+use reversi::{Bitboard, Book, BookError, Engine, Game, History, Player};
+
+
+// Square index of a square name like "f5"
+fn sq(name: &str) -> usize {
+    let (row, col) = parse_square_name(name).unwrap();
+    square(row, col)
+}
+
+// Test that a move learned in one position is found in all its symmetric
+// copies
+#[test]
+fn test_symmetric_positions_share_moves() {
+    let book = Book::from_transcripts(["f5d6"], 2).unwrap();
+    assert_eq!(book.len(), 2);
+
+    let start = Bitboard::initial();
+    assert_eq!(book.moves(&start, Player::Black), vec![(sq("f5"), 1)]);
+
+    // e6, c4 and d3 lead to copies of the position after f5; d6 answers f5
+    // and the same reply is mirrored for the others
+    for (first, reply) in [("f5", "d6"), ("e6", "f4"), ("c4", "e3"), ("d3", "c5")] {
+        let history = History::from_transcript(first).unwrap();
+        let board = Bitboard::from_grid(history.game().grid());
+        assert_eq!(book.moves(&board, Player::White), vec![(sq(reply), 1)], "after {}", first);
+    }
+}
+
+// Test that weights add up and only book moves are chosen
+#[test]
+fn test_weighted_choice() {
+    let book = Book::from_transcripts(["f5d6", "f5f6", "f5d6c3"], 3).unwrap();
+    let mut after_f5 = Game::new();
+    after_f5.play(4, 5);
+    let board = Bitboard::from_grid(after_f5.grid());
+    assert_eq!(book.moves(&board, Player::White), vec![(sq("d6"), 2), (sq("f6"), 1)]);
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut counts = [0; 2];
+    for _ in 0..300 {
+        match book.choose(&board, Player::White, &mut rng) {
+            Some(m) if m == sq("d6") => counts[0] += 1,
+            Some(m) if m == sq("f6") => counts[1] += 1,
+            other => panic!("unexpected book move {:?}", other),
+        }
+    }
+    assert!(counts[0] > counts[1] && counts[1] > 0, "{:?}", counts);
+
+    // Weights too big to add up in a u32 are still chosen from fairly
+    let mut heavy = Book::new();
+    heavy.add(&board, Player::White, sq("d6"), u32::MAX);
+    heavy.add(&board, Player::White, sq("f6"), u32::MAX);
+    heavy.add(&board, Player::White, sq("f6"), 1);
+    assert_eq!(heavy.moves(&board, Player::White), vec![(sq("d6"), u32::MAX), (sq("f6"), u32::MAX)]);
+    let picks: Vec<usize> = (0..20).filter_map(|_| heavy.choose(&board, Player::White, &mut rng)).collect();
+    assert_eq!(picks.len(), 20);
+    assert!(picks.contains(&sq("d6")) && picks.contains(&sq("f6")), "{:?}", picks);
+
+    // A position outside the book has no move
+    let mut game = after_f5.clone();
+    game.play(5, 5);
+    game.play(4, 2);
+    assert_eq!(book.choose(&Bitboard::from_grid(game.grid()), Player::White, &mut rng), None);
+}
+
+// Test that a book survives a round trip through its file format
+#[test]
+fn test_book_file_round_trip() {
+    let book = Book::from_transcripts(["f5d6c3d3c4", "f5f6e6f4", "d3c5"], 4).unwrap();
+    let text = book.to_string();
+    assert_eq!(Book::parse(&text), Ok(book.clone()));
+    assert_eq!(Book::parse(&format!("# my book\n\n{}", text)).unwrap(), book);
+}
+
+// Test that positions with blocked squares are kept apart from the same
+// discs on an open board, in memory and in the file format
+#[test]
+fn test_blocked_positions_separate() {
+    let mut book = Book::from_transcripts(["f5"], 1).unwrap();
+    let open = Bitboard::initial();
+    let blocked = Bitboard { blocked: 1, ..open };
+    assert_eq!(book.moves(&blocked, Player::Black), vec![]);
+
+    book.add(&blocked, Player::Black, sq("d3"), 2);
+    assert_eq!(book.len(), 2);
+    assert_eq!(book.moves(&open, Player::Black), vec![(sq("f5"), 1)]);
+    assert_eq!(book.moves(&blocked, Player::Black), vec![(sq("d3"), 2)]);
+    assert_eq!(Book::parse(&book.to_string()), Ok(book));
+}
+
+// Test the errors for bad book lines and games
+#[test]
+fn test_book_errors() {
+    let start = "...........................WB......BW........................... B";
+    assert!(matches!(Book::parse("BW. B f5:1"), Err(BookError::BadPosition { line: 1, .. })));
+    assert_eq!(
+        Book::parse(&format!("{} f5", start)),
+        Err(BookError::BadMove { line: 1, text: "f5".to_string() })
+    );
+    assert_eq!(
+        Book::parse(&format!("\n{} a1:3", start)),
+        Err(BookError::IllegalMove { line: 2, text: "a1".to_string() })
+    );
+    assert_eq!(
+        Book::parse(&format!("{} f5:4294967295\n{} f5:1", start, start)),
+        Err(BookError::WeightOverflow { line: 2, text: "f5".to_string() })
+    );
+    assert!(matches!(Book::parse(&format!("{} f5:4294967296", start)), Err(BookError::BadMove { line: 1, .. })));
+    assert!(matches!(
+        Book::from_transcripts(["f5", "f5a1"], 10),
+        Err(BookError::BadGame { game: 2, .. })
+    ));
+}
+
+// Test that the engine plays from its book before searching
+#[test]
+fn test_engine_uses_book() {
+    let mut engine = Engine::default();
+    engine.set_book(Book::from_transcripts(["c4"], 1).unwrap(), 1);
+    assert_eq!(engine.choose_move(&Game::new()), Some((3, 2)));
+    assert_eq!(engine.book().map(Book::len), Some(1));
+}
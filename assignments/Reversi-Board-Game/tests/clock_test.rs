@@ -0,0 +1,147 @@
+// Import game clocks, time controls and the engine's clock budget
+use reversi::clock::{budget, format_clock, TimeControl};
+use reversi::{Clock, Engine, Game, Heuristic, Limit, Player};
+use std::thread;
+use std::time::{Duration, Instant};
+
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+// Test the ways a time control can be written
+#[test]
+fn test_parse_time_control() {
+    assert_eq!(TimeControl::parse("5m").unwrap(), TimeControl::sudden_death(secs(300)));
+    assert_eq!(TimeControl::parse("5").unwrap(), TimeControl::sudden_death(secs(300)));
+    assert_eq!(TimeControl::parse("90s").unwrap(), TimeControl::sudden_death(secs(90)));
+    let blitz = TimeControl { initial: secs(180), increment: secs(2) };
+    assert_eq!(TimeControl::parse("3m+2s").unwrap(), blitz);
+    assert_eq!(TimeControl::parse("3+2").unwrap(), blitz);
+    assert_eq!(TimeControl::parse(" 3 + 2s ").unwrap(), blitz);
+    assert_eq!(TimeControl::parse("0.5m+1.5").unwrap(), TimeControl { initial: secs(30), increment: Duration::from_millis(1500) });
+
+    for text in ["", "m", "0", "-1m", "5h", "3+", "3+x", "1+2+3"] {
+        assert!(TimeControl::parse(text).is_err(), "{:?}", text);
+    }
+    assert_eq!(TimeControl::parse("5h").unwrap_err().to_string(), "'5h' is not a time control, like 5m or 3m+2s");
+}
+
+// Test that controls too long for a duration are errors, not panics
+#[test]
+fn test_huge_time_control() {
+    for text in ["1e300", "1e300s", "5m+1e300", "inf", "NaN", "5m+NaN"] {
+        assert!(TimeControl::parse(text).is_err(), "{:?}", text);
+    }
+    assert!(TimeControl::parse("1e9").is_ok());
+}
+
+// Test that a time control is written back in a form that parses to itself
+#[test]
+fn test_time_control_display() {
+    for (text, shown) in [("5m", "5m"), ("3+2", "3m+2s"), ("90s+0", "90s"), ("0.5m+1.5", "30s+1.5s")] {
+        let control = TimeControl::parse(text).unwrap();
+        assert_eq!(control.to_string(), shown);
+        assert_eq!(TimeControl::parse(shown).unwrap(), control);
+    }
+}
+
+// Test how clock times are shown
+#[test]
+fn test_format_clock() {
+    assert_eq!(format_clock(secs(300)), "5:00");
+    assert_eq!(format_clock(Duration::from_millis(65_900)), "1:05");
+    assert_eq!(format_clock(secs(10)), "0:10");
+    assert_eq!(format_clock(Duration::from_millis(9_870)), "0:09.8");
+    assert_eq!(format_clock(Duration::ZERO), "0:00.0");
+}
+
+// Test that moves use up time and earn the increment
+#[test]
+fn test_charge_with_increment() {
+    let mut clock = Clock::new(TimeControl { initial: secs(60), increment: secs(5) });
+    assert!(clock.charge(Player::Black, secs(10)));
+    assert_eq!(clock.remaining(Player::Black), secs(55));
+    assert_eq!(clock.remaining(Player::White), secs(60));
+    assert!(clock.charge(Player::White, secs(1)));
+    assert_eq!(clock.remaining(Player::White), secs(64));
+    assert_eq!(clock.flagged(), None);
+    assert_eq!(clock.to_string(), "Clock: Black 0:55  White 1:04");
+}
+
+// Test that running out of time flags the player and stops the clock at zero
+#[test]
+fn test_timeout() {
+    let mut clock = Clock::new(TimeControl::sudden_death(secs(30)));
+    assert!(clock.charge(Player::White, secs(20)));
+    assert!(!clock.charge(Player::White, secs(10)));
+    assert_eq!(clock.remaining(Player::White), Duration::ZERO);
+    assert_eq!(clock.flagged(), Some(Player::White));
+
+    // The first player to run out stays the loser
+    assert!(!clock.charge(Player::Black, secs(40)));
+    assert_eq!(clock.flagged(), Some(Player::White));
+}
+
+// Test that a running clock counts down and is charged when stopped
+#[test]
+fn test_start_and_stop() {
+    let mut clock = Clock::new(TimeControl::sudden_death(secs(10)));
+    assert_eq!(clock.running(), None);
+    assert!(clock.stop());
+
+    clock.start(Player::Black);
+    assert_eq!(clock.running(), Some(Player::Black));
+    thread::sleep(Duration::from_millis(30));
+    assert!(clock.remaining(Player::Black) <= Duration::from_millis(9_970));
+
+    // Starting the other side ends Black's turn
+    clock.start(Player::White);
+    assert_eq!(clock.running(), Some(Player::White));
+    let black = clock.remaining(Player::Black);
+    assert!(black <= Duration::from_millis(9_970));
+    thread::sleep(Duration::from_millis(10));
+    assert_eq!(clock.remaining(Player::Black), black);
+
+    assert!(clock.stop());
+    assert_eq!(clock.running(), None);
+    assert!(clock.remaining(Player::White) < secs(10));
+}
+
+// Test that a player is flagged as soon as the running clock reaches zero
+#[test]
+fn test_flag_while_running() {
+    let mut clock = Clock::new(TimeControl::sudden_death(Duration::from_millis(20)));
+    clock.start(Player::Black);
+    thread::sleep(Duration::from_millis(40));
+    assert_eq!(clock.flagged(), Some(Player::Black));
+    assert!(!clock.stop());
+    assert_eq!(clock.flagged(), Some(Player::Black));
+}
+
+// Test that the move budget spreads the time over the game and keeps a reserve
+#[test]
+fn test_budget() {
+    // Half of the 60 empty squares are the mover's
+    assert_eq!(budget(secs(310), Duration::ZERO, 60), secs(10));
+    assert_eq!(budget(secs(310), secs(4), 60), secs(13));
+    // Fewer empty squares leave more time for each move
+    assert!(budget(secs(60), Duration::ZERO, 10) > budget(secs(60), Duration::ZERO, 40));
+    // Never more than half of what is left
+    assert_eq!(budget(secs(10), secs(20), 2), secs(5));
+    assert_eq!(budget(secs(10), Duration::ZERO, 0), secs(5));
+    assert_eq!(budget(Duration::ZERO, secs(2), 30), Duration::ZERO);
+}
+
+// Test that the engine keeps to its budget when playing on a clock
+#[test]
+fn test_engine_on_clock() {
+    let game = Game::new();
+    let limit = Limit::Clock { remaining: Duration::from_millis(600), increment: Duration::ZERO };
+    let mut engine = Engine::new(Heuristic::default(), limit);
+    let start = Instant::now();
+    let (row, col) = engine.choose_move(&game).unwrap();
+    // 600ms over 31 moves is under 20ms a move
+    assert!(start.elapsed() < Duration::from_millis(300), "took {:?}", start.elapsed());
+    assert!(game.is_legal(row, col));
+}
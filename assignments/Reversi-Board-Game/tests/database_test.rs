@@ -0,0 +1,145 @@
+// Import the game database and check its statistics
+use reversi::database::Outcomes;
+use reversi::history::TranscriptError;
+use reversi::{Database, DatabaseError, Game, History, Move, Player};
+
+
+// The position after a transcript
+fn after(transcript: &str) -> Game {
+    History::from_transcript(transcript).unwrap().game().clone()
+}
+
+// A complete game in which each side plays its `pick`th legal move (wrapping
+// around), as a transcript
+fn full_game(pick: usize) -> String {
+    let mut history = History::new();
+    while !history.game().is_over() {
+        if history.game().must_pass() {
+            history.pass();
+            continue;
+        }
+        let moves = history.game().legal_moves();
+        let (row, col) = moves[pick % moves.len()];
+        history.play(row, col);
+    }
+    history.to_transcript()
+}
+
+// Test that an empty database knows nothing
+#[test]
+fn test_empty_database() {
+    let database = Database::new();
+    assert!(database.is_empty());
+    assert_eq!(database.positions(), 0);
+    let stats = database.stats(&Game::new());
+    assert_eq!(stats.outcomes, Outcomes::default());
+    assert!(stats.continuations.is_empty());
+    assert_eq!(stats.outcomes.win_rate(Player::Black), None);
+}
+
+// Test that symmetric openings are counted as the same position, with the
+// moves shown for the position asked about
+#[test]
+fn test_symmetric_openings() {
+    let database = Database::parse("f5d6\nd3c5\nc4e3\nf5f6\n").unwrap();
+    assert_eq!(database.len(), 4);
+
+    // Every opening move leads to the same position up to symmetry
+    for opening in ["f5", "d3", "c4", "e6"] {
+        assert_eq!(database.stats(&after(opening)).outcomes.games(), 4, "{}", opening);
+    }
+
+    let stats = database.stats(&after("f5"));
+    let moves: Vec<(Move, u32)> = stats.continuations.iter().map(|c| (c.mv, c.outcomes.games())).collect();
+    assert_eq!(moves, [(Move::Play(5, 3), 3), (Move::Play(5, 5), 1)]);
+
+    let stats = database.stats(&after("d3"));
+    let moves: Vec<(Move, u32)> = stats.continuations.iter().map(|c| (c.mv, c.outcomes.games())).collect();
+    assert_eq!(moves, [(Move::Play(4, 2), 3), (Move::Play(2, 2), 1)]);
+
+    assert_eq!(database.stats(&after("f5d6")).outcomes.games(), 3);
+    assert_eq!(database.stats(&after("f5d6c3")).outcomes.games(), 0);
+}
+
+// Test the results of finished games at the start and after their moves
+#[test]
+fn test_finished_games() {
+    let games: Vec<String> = (0..6).map(full_game).collect();
+    let mut database = Database::new();
+    let mut expected = Outcomes::default();
+    for transcript in &games {
+        database.add_transcript(transcript).unwrap();
+        match after(transcript).winner() {
+            Some(Player::Black) => expected.black_wins += 1,
+            Some(Player::White) => expected.white_wins += 1,
+            None => expected.draws += 1,
+        }
+    }
+
+    let stats = database.stats(&Game::new());
+    assert_eq!(stats.outcomes, expected);
+    assert_eq!(stats.outcomes.games(), 6);
+    let rate = stats.outcomes.win_rate(Player::Black).unwrap();
+    assert_eq!(rate, expected.black_wins as f64 / 6.0);
+    let sum = rate + stats.outcomes.win_rate(Player::White).unwrap() + stats.outcomes.draw_rate().unwrap();
+    assert!((sum - 1.0).abs() < 1e-9);
+
+    // The continuations split the games that reached the position
+    let total: Outcomes = stats.continuations.iter().fold(Outcomes::default(), |mut total, c| {
+        total.black_wins += c.outcomes.black_wins;
+        total.white_wins += c.outcomes.white_wins;
+        total.draws += c.outcomes.draws;
+        total
+    });
+    assert_eq!(total, expected);
+
+    // The final position of each game is known, with nothing played after it
+    for transcript in &games {
+        let stats = database.stats(&after(transcript));
+        assert!(stats.outcomes.finished() >= 1);
+        assert!(stats.continuations.is_empty());
+    }
+}
+
+// Test that unfinished games are counted but have no result
+#[test]
+fn test_unfinished_games() {
+    let mut database = Database::parse("f5d6c3\n").unwrap();
+    database.add_transcript(&full_game(0)).unwrap();
+    let stats = database.stats(&Game::new());
+    assert_eq!(stats.outcomes.games(), 2);
+    assert_eq!(stats.outcomes.unfinished, 1);
+    assert_eq!(stats.outcomes.finished(), 1);
+
+    let stats = database.stats(&after("f5d6"));
+    assert_eq!(stats.outcomes.unfinished, 1);
+    let c3 = stats.continuations.iter().find(|c| c.mv == Move::Play(2, 2)).unwrap();
+    assert_eq!(c3.outcomes, Outcomes { unfinished: 1, ..Outcomes::default() });
+}
+
+// Test that a database survives being written out and read back
+#[test]
+fn test_round_trip() {
+    let mut database = Database::new();
+    let added = database.add_text("# openings\nf5d6c3\n\n  d3c3  \n").unwrap();
+    assert_eq!(added, 2);
+    let text = database.to_string();
+    assert_eq!(text, "f5d6c3\nd3c3\n");
+    assert_eq!(Database::parse(&text).unwrap(), database);
+    assert_eq!(database.transcripts(), ["f5d6c3", "d3c3"]);
+}
+
+// Test that a bad game is reported with its line and nothing is added
+#[test]
+fn test_bad_games() {
+    let mut database = Database::parse("f5d6\n").unwrap();
+    let err = database.add_text("f5\n# fine so far\nf5f5\n").unwrap_err();
+    assert_eq!(
+        err,
+        DatabaseError { line: 3, error: TranscriptError::IllegalMove { number: 2, text: "f5".to_string() } }
+    );
+    assert_eq!(err.to_string(), "line 3: move 2: 'f5' is not a legal move");
+    assert_eq!(database.len(), 1);
+    assert_eq!(database.stats(&Game::new()).outcomes.games(), 1);
+    assert!(Database::parse("z9").is_err());
+}
@@ -0,0 +1,270 @@
+// Import the search engine and the bitboard it searches on
+use std::time::{Duration, Instant};
+
+use reversi::bitboard::{self, squares, Bitboard};
+use reversi::engine::{final_score, final_score_for_goal, SearchResult, WIN_SCORE};
+use reversi::{Cell, Engine, Evaluate, Game, Goal, Heuristic, Limit, Move, Player, SIZE};
+
+
+// Plain minimax without pruning, used as a reference for alpha-beta
+fn minimax(eval: &Heuristic, board: &Bitboard, player: Player, depth: u32) -> i32 {
+    let moves = board.legal_moves(player);
+    if moves == 0 {
+        if board.legal_moves(player.opponent()) == 0 {
+            return final_score(board, player);
+        }
+        return -minimax(eval, board, player.opponent(), depth);
+    }
+    if depth == 0 {
+        return eval.evaluate(board, player);
+    }
+    squares(moves)
+        .map(|sq| {
+            let mut child = *board;
+            child.apply(player, sq);
+            -minimax(eval, &child, player.opponent(), depth - 1)
+        })
+        .max()
+        .unwrap()
+}
+
+// Minimax as above, scoring finished games by `goal`
+fn goal_minimax(eval: &Heuristic, board: &Bitboard, player: Player, depth: u32, goal: Goal) -> i32 {
+    let moves = board.legal_moves(player);
+    if moves == 0 {
+        if board.legal_moves(player.opponent()) == 0 {
+            return final_score_for_goal(board, player, goal);
+        }
+        return -goal_minimax(eval, board, player.opponent(), depth, goal);
+    }
+    if depth == 0 {
+        return eval.evaluate(board, player);
+    }
+    squares(moves)
+        .map(|sq| {
+            let mut child = *board;
+            child.apply(player, sq);
+            -goal_minimax(eval, &child, player.opponent(), depth - 1, goal)
+        })
+        .max()
+        .unwrap()
+}
+
+// Test that alpha-beta finds the same score as full minimax
+#[test]
+fn test_alpha_beta_matches_minimax() {
+    let mut game = Game::new();
+    for (row, col) in [(2, 3), (2, 2), (3, 2)] {
+        assert!(game.play(row, col));
+    }
+    let board = Bitboard::from_grid(game.grid());
+    for depth in 1..=4 {
+        let mut engine = Engine::new(Heuristic::default(), Limit::Depth(depth));
+        let result = engine.search(&board, game.to_move());
+        assert_eq!(result.depth, depth);
+        assert_eq!(result.score, minimax(&Heuristic::default(), &board, game.to_move(), depth));
+    }
+}
+
+// Test that anti-reversi scores finished games and discs the other way
+#[test]
+fn test_fewest_discs_goal() {
+    let board = Bitboard::from_grid(Game::from_position(&format!("BBW{} B", ".".repeat(61))).unwrap().grid());
+    assert_eq!(final_score(&board, Player::Black), WIN_SCORE + 1);
+    assert_eq!(final_score_for_goal(&board, Player::Black, Goal::MostDiscs), WIN_SCORE + 1);
+    assert_eq!(final_score_for_goal(&board, Player::Black, Goal::FewestDiscs), -WIN_SCORE - 1);
+    let anti = Heuristic::for_goal(Goal::FewestDiscs);
+    assert_eq!(anti, Heuristic { discs: -1, ..Heuristic::default() });
+    assert_eq!(Heuristic::for_goal(Goal::MostDiscs), Heuristic::default());
+
+    // Play into the endgame, then search to the end under both goals
+    let mut game = Game::new();
+    while game.grid().iter().flatten().filter(|&&cell| cell == Cell::Empty).count() > 9 {
+        if game.must_pass() {
+            game.pass();
+            continue;
+        }
+        let (row, col) = game.legal_moves()[0];
+        game.play(row, col);
+    }
+    let board = Bitboard::from_grid(game.grid());
+    for (goal, eval) in [(Goal::MostDiscs, Heuristic::default()), (Goal::FewestDiscs, anti)] {
+        let mut engine = Engine::new(eval, Limit::Depth(3));
+        engine.set_goal(goal);
+        assert_eq!(engine.goal(), goal);
+        let result = engine.search(&board, game.to_move());
+        assert_eq!(result.score, goal_minimax(&eval, &board, game.to_move(), 3, goal), "{}", goal);
+    }
+    let mut engine = Engine::new(anti, Limit::Depth(9));
+    engine.set_goal(Goal::FewestDiscs);
+    let anti_result = engine.search(&board, game.to_move()).score;
+    let result = Engine::new(Heuristic::default(), Limit::Depth(9)).search(&board, game.to_move()).score;
+    // Both searches reach the end of the game, where the goals disagree
+    assert!(anti_result.abs() >= WIN_SCORE || anti_result == 0);
+    assert_ne!(anti_result, result);
+}
+
+// Test that the engine grabs a free corner
+#[test]
+fn test_takes_corner() {
+    let mut board = [['.'; SIZE]; SIZE];
+    board[0][1] = 'W'; // ab
+    board[0][2] = 'B'; // ac
+    board[4][4] = 'W';
+    board[4][5] = 'B';
+    let mut engine = Engine::default();
+    let result = engine.search(&Bitboard::from_board(&board), Player::Black);
+    assert_eq!(result.best_move, Some(bitboard::square(0, 0)));
+}
+
+// Test that a side without moves gets a pass
+#[test]
+fn test_no_moves_is_pass() {
+    let mut board = [['.'; SIZE]; SIZE];
+    board[0][0] = 'W';
+    board[0][1] = 'B';
+    let mut engine = Engine::default();
+    let result = engine.search(&Bitboard::from_board(&board), Player::Black);
+    assert_eq!(result, SearchResult { best_move: None, score: 0, depth: 0, nodes: 0 });
+}
+
+// Test that a custom evaluation can be plugged in
+#[test]
+fn test_custom_evaluation() {
+    // Greedy disc count at depth 1 picks the move flipping the most discs
+    let greedy = |board: &Bitboard, player: Player| {
+        board.discs(player).count_ones() as i32 - board.discs(player.opponent()).count_ones() as i32
+    };
+    let mut board = [['.'; SIZE]; SIZE];
+    board[3][0] = 'B';
+    board[3][1] = 'W';
+    board[3][2] = 'W';
+    board[5][0] = 'B';
+    board[5][1] = 'W';
+    let mut engine = Engine::new(greedy, Limit::Depth(1));
+    let result = engine.search(&Bitboard::from_board(&board), Player::Black);
+    assert_eq!(result.best_move, Some(bitboard::square(3, 3)));
+}
+
+// Test that a timed search returns a legal move without overrunning badly
+#[test]
+fn test_time_limit() {
+    let game = Game::new();
+    let mut engine = Engine::new(Heuristic::default(), Limit::Time(Duration::from_millis(50)));
+    let start = Instant::now();
+    let (row, col) = engine.choose_move(&game).unwrap();
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(game.is_legal(row, col));
+}
+
+// Test that parallel search picks the same move with the same score as a
+// single thread at every fixed depth
+#[test]
+fn test_parallel_matches_sequential() {
+    let openings = ["", "f5d6c3d3c4f4", "f5f6e6f4e3c5c4e7", "c4e3f6e6f5c5f4g6f7d3"];
+    for transcript in openings {
+        let history = reversi::History::from_transcript(transcript).unwrap();
+        let board = Bitboard::from_grid(history.game().grid());
+        let player = history.game().to_move();
+        for depth in 1..=5 {
+            let mut single = Engine::new(Heuristic::default(), Limit::Depth(depth));
+            let mut parallel = single.clone();
+            parallel.set_threads(4);
+            assert_eq!(parallel.threads(), 4);
+            let (one, many) = (single.search(&board, player), parallel.search(&board, player));
+            assert_eq!((one.best_move, one.score, one.depth), (many.best_move, many.score, many.depth), "{} at depth {}", transcript, depth);
+        }
+    }
+
+    let mut engine = Engine::default();
+    assert_eq!(engine.threads(), 1);
+    engine.set_threads(1);
+    assert_eq!(engine.threads(), 1);
+}
+
+// Test that a timed parallel search still returns a legal move
+#[test]
+fn test_parallel_time_limit() {
+    let game = Game::new();
+    let mut engine = Engine::new(Heuristic::default(), Limit::Time(Duration::from_millis(50)));
+    engine.set_threads(3);
+    let start = Instant::now();
+    let (row, col) = engine.choose_move(&game).unwrap();
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(game.is_legal(row, col));
+}
+
+// Test that analysis scores every move, agrees with the search on the best
+// score and gives principal variations that can be played out
+#[test]
+fn test_analyze() {
+    let history = reversi::History::from_transcript("f5d6c3d3c4").unwrap();
+    let game = history.game();
+    let board = Bitboard::from_grid(game.grid());
+    let player = game.to_move();
+
+    let mut engine = Engine::new(Heuristic::default(), Limit::Depth(4));
+    let mut depths = Vec::new();
+    let analysis = engine.analyze(&board, player, |analysis| depths.push(analysis.depth));
+    assert_eq!(depths, vec![1, 2, 3, 4]);
+    assert_eq!(analysis.depth, 4);
+    assert_eq!(analysis.variations.len(), game.legal_moves().len());
+    assert!(analysis.variations.windows(2).all(|pair| pair[0].score >= pair[1].score));
+
+    let result = engine.search(&board, player);
+    assert_eq!(analysis.variations[0].score, result.score);
+
+    for variation in &analysis.variations {
+        assert_eq!(variation.moves.len(), 4, "{:?}", variation);
+        let mut line = history.clone();
+        for &mv in &variation.moves {
+            assert!(line.apply(mv), "{:?} in {:?}", mv, variation);
+        }
+        // Each move's score is what a search of the position after it gives
+        let Move::Play(row, col) = variation.moves[0] else { panic!("root move is a pass") };
+        let mut after = board;
+        after.apply(player, bitboard::square(row, col));
+        engine.set_limit(Limit::Depth(3));
+        assert_eq!(-engine.search(&after, player.opponent()).score, variation.score);
+        engine.set_limit(Limit::Depth(4));
+    }
+}
+
+// Test that analysis stops once the end of the game is reached and
+// includes forced passes in its lines
+#[test]
+fn test_analyze_endgame() {
+    let position = "WW.BBBBBWWWWBBB.WWWBBBB.WWWWWBBWWWWWBW.BBWWBBWBWBWWWWBWW.BBBBWWW W";
+    let start = Game::from_position(position).unwrap();
+    let board = Bitboard::from_grid(start.grid());
+    let engine = Engine::new(Heuristic::default(), Limit::Depth(20));
+    let analysis = engine.analyze(&board, Player::White, |_| {});
+    assert!(analysis.depth <= board.empty().count_ones());
+
+    let best = &analysis.variations[0];
+    assert!(best.score >= reversi::engine::WIN_SCORE, "{:?}", best);
+    assert!(best.moves.contains(&Move::Pass), "{:?}", best);
+    for variation in &analysis.variations {
+        let mut line = reversi::History::from_game(start.clone());
+        for &mv in &variation.moves {
+            assert!(line.apply(mv), "{:?} in {:?}", mv, variation);
+        }
+    }
+
+    // The side to move has no move at all
+    let full = Bitboard::from_board(&[['W'; 8]; 8]);
+    let analysis = engine.analyze(&full, Player::Black, |_| panic!("no iteration to report"));
+    assert_eq!(analysis.variations, Vec::new());
+}
+
+// Test that parallel analysis gives the same lines as a single thread
+#[test]
+fn test_parallel_analyze() {
+    let history = reversi::History::from_transcript("f5f6e6f4e3").unwrap();
+    let board = Bitboard::from_grid(history.game().grid());
+    let player = history.game().to_move();
+    let single = Engine::new(Heuristic::default(), Limit::Depth(4));
+    let mut parallel = single.clone();
+    parallel.set_threads(3);
+    assert_eq!(single.analyze(&board, player, |_| {}).variations, parallel.analyze(&board, player, |_| {}).variations);
+}
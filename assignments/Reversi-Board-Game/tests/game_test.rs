@@ -0,0 +1,103 @@
+// Import the typed game API from the lib.rs module
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use reversi::{create_initial_board, is_valid_move, apply_move, Cell, Game, Player, SIZE};
+
+
+// Test that Player and Cell round-trip through their board characters
+#[test]
+fn test_player_and_cell_chars() {
+    assert_eq!(Player::from_char('B'), Some(Player::Black));
+    assert_eq!(Player::from_char('W'), Some(Player::White));
+    assert_eq!(Player::from_char('b'), None); // Lowercase is not a player
+    assert_eq!(Player::from_char('X'), None);
+    assert_eq!(Player::Black.opponent(), Player::White);
+
+    assert_eq!(Cell::from_char('.'), Some(Cell::Empty));
+    assert_eq!(Cell::from(Player::White).to_char(), 'W');
+    assert_eq!(Cell::Black.player(), Some(Player::Black));
+}
+
+// Test that the legacy wrappers ignore unknown player characters
+#[test]
+fn test_wrappers_reject_unknown_player() {
+    let mut board = create_initial_board();
+    assert!(!is_valid_move(&board, 'b', 2, 3));
+    apply_move(&mut board, 'X', 2, 3);
+    assert_eq!(board, create_initial_board());
+}
+
+// Test that Game starts from the standard position with Black to move
+#[test]
+fn test_new_game() {
+    let game = Game::new();
+    assert_eq!(game.board(), create_initial_board());
+    assert_eq!(game.to_move(), Player::Black);
+    assert_eq!(game.legal_moves(), vec![(2, 3), (3, 2), (4, 5), (5, 4)]);
+    assert!(!game.is_over());
+}
+
+// Test that playing a move flips discs and hands over the turn
+#[test]
+fn test_play() {
+    let mut game = Game::new();
+    assert!(game.play(2, 3)); // c4
+    assert_eq!(game.cell(3, 3), Cell::Black);
+    assert_eq!(game.to_move(), Player::White);
+    assert_eq!(game.score(), (4, 1));
+
+    // Illegal and off-board moves leave the game untouched
+    let before = game.clone();
+    assert!(!game.play(0, 0));
+    assert!(!game.play(SIZE, 0));
+    assert_eq!(game, before);
+}
+
+// Test pass handling when only one side is stuck
+#[test]
+fn test_pass() {
+    // Black cannot move, White can still capture at ac
+    let mut board = [['.'; SIZE]; SIZE];
+    board[0][0] = 'W';
+    board[0][1] = 'B';
+    let mut game = Game::from_board(&board, Player::Black);
+    assert!(game.must_pass());
+    assert!(!game.play(0, 2));
+    assert!(game.pass());
+    assert_eq!(game.to_move(), Player::White);
+    assert!(!game.pass()); // White has a move, so it may not pass
+    assert!(game.play(0, 2));
+    assert!(game.is_over());
+    assert_eq!(game.winner(), Some(Player::White));
+}
+
+// Test game-over detection on a full board
+#[test]
+fn test_game_over() {
+    let board = [['B'; SIZE]; SIZE];
+    let mut game = Game::from_board(&board, Player::White);
+    assert!(game.is_over());
+    assert!(!game.must_pass());
+    assert!(!game.pass());
+    assert_eq!(game.winner(), Some(Player::Black));
+}
+
+// Test that random moves are legal, pass when forced and stop at the end
+#[test]
+fn test_play_random() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut game = Game::new();
+    game.play_random(6, &mut rng);
+    let (black, white) = game.score();
+    assert_eq!(black + white, 10);
+    assert_eq!(game.to_move(), Player::Black);
+
+    // Black must pass, then White plays the only move and the game ends
+    let mut board = [['.'; SIZE]; SIZE];
+    board[0][0] = 'W';
+    board[0][1] = 'B';
+    let mut game = Game::from_board(&board, Player::Black);
+    game.play_random(5, &mut rng);
+    assert!(game.is_over());
+    assert_eq!(game.board()[0][2], 'W');
+}
@@ -0,0 +1,100 @@
+// Import the move history and transcript functions from the lib.rs module
+use reversi::history::{parse_square_name, square_name, TranscriptError};
+use reversi::{Game, History, Move, Player, SIZE};
+
+
+// Test the standard square notation used in transcripts
+#[test]
+fn test_square_names() {
+    assert_eq!(square_name(4, 5), "f5");
+    assert_eq!(parse_square_name("f5"), Some((4, 5)));
+    assert_eq!(parse_square_name("A1"), Some((0, 0)));
+    assert_eq!(parse_square_name("i1"), None);
+    assert_eq!(parse_square_name("a9"), None);
+    assert_eq!(parse_square_name("a0"), None);
+}
+
+// Test undo and redo of played moves
+#[test]
+fn test_undo_redo() {
+    let mut history = History::new();
+    assert!(history.play(4, 5)); // f5
+    assert!(history.play(5, 3)); // d6
+    let after_two = history.game().clone();
+
+    assert_eq!(history.undo(), Some(Move::Play(5, 3)));
+    assert_eq!(history.game().to_move(), Player::White);
+    assert_eq!(history.undo(), Some(Move::Play(4, 5)));
+    assert_eq!(history.game(), &Game::new());
+    assert_eq!(history.undo(), None);
+
+    assert_eq!(history.redo(), Some(Move::Play(4, 5)));
+    assert_eq!(history.redo(), Some(Move::Play(5, 3)));
+    assert_eq!(history.game(), &after_two);
+    assert_eq!(history.redo(), None);
+}
+
+// Test that a new move after undo discards the redo branch
+#[test]
+fn test_play_after_undo() {
+    let mut history = History::new();
+    assert!(history.play(4, 5));
+    assert!(history.play(5, 3));
+    history.undo();
+    assert!(history.play(5, 5)); // f6
+    assert!(!history.can_redo());
+    assert_eq!(history.moves(), &[Move::Play(4, 5), Move::Play(5, 5)]);
+    assert!(!history.play(0, 0)); // Illegal moves are not recorded
+    assert_eq!(history.moves().len(), 2);
+}
+
+// Test that passes are recorded, undone and left out of transcripts
+#[test]
+fn test_pass_history() {
+    let mut board = [['.'; SIZE]; SIZE];
+    board[0][0] = 'W';
+    board[0][1] = 'B';
+    let mut history = History::from_game(Game::from_board(&board, Player::Black));
+    assert!(history.pass());
+    assert!(history.play(0, 2));
+    assert_eq!(history.moves(), &[Move::Pass, Move::Play(0, 2)]);
+    assert_eq!(history.to_transcript(), "c1");
+
+    assert_eq!(history.undo(), Some(Move::Play(0, 2)));
+    assert_eq!(history.undo(), Some(Move::Pass));
+    assert_eq!(history.game().to_move(), Player::Black);
+
+    // The pass is implied when the transcript is replayed
+    let replayed = History::from_transcript_at(history.start().clone(), "c1").unwrap();
+    assert_eq!(replayed.moves(), &[Move::Pass, Move::Play(0, 2)]);
+}
+
+// Test transcript export and import round trip
+#[test]
+fn test_transcript_round_trip() {
+    let history = History::from_transcript("f5d6c3d3c4").unwrap();
+    assert_eq!(history.moves().len(), 5);
+    assert_eq!(history.to_transcript(), "f5d6c3d3c4");
+    assert_eq!(history.game().to_move(), Player::White);
+
+    // Case and whitespace do not matter
+    let spaced = History::from_transcript("F5 D6\nC3 d3 c4\n").unwrap();
+    assert_eq!(spaced, history);
+}
+
+// Test that bad transcripts report the offending move
+#[test]
+fn test_transcript_errors() {
+    assert_eq!(
+        History::from_transcript("f5z9"),
+        Err(TranscriptError::BadSquare { number: 2, text: "z9".to_string() })
+    );
+    assert_eq!(
+        History::from_transcript("f5f5"),
+        Err(TranscriptError::IllegalMove { number: 2, text: "f5".to_string() })
+    );
+    assert_eq!(
+        History::from_transcript("pa"),
+        Err(TranscriptError::IllegalMove { number: 1, text: "pa".to_string() })
+    );
+}
@@ -0,0 +1,95 @@
+// Import the Result-returning move functions and their MoveError reasons
+use reversi::{
+    apply_move, check_move, create_initial_board, is_valid_move, parse_input, try_apply_move, try_parse_input,
+    try_parse_input_sized, Game, History, MoveError, Player,
+};
+
+
+// Test that parsing tells bad input apart from squares off the board
+#[test]
+fn test_try_parse_input() {
+    assert_eq!(try_parse_input("cd"), Ok((2, 3)));
+    assert_eq!(try_parse_input("HH"), Ok((7, 7)));
+    assert_eq!(try_parse_input("ai"), Err(MoveError::OffBoard { row: 0, col: 8 }));
+    assert_eq!(try_parse_input("zz"), Err(MoveError::OffBoard { row: 25, col: 25 }));
+    assert_eq!(try_parse_input_sized("ii", 10), Ok((8, 8)));
+    assert_eq!(try_parse_input_sized("ii", 6), Err(MoveError::OffBoard { row: 8, col: 8 }));
+    for text in ["", "c", "cde", "c4", "4c", "c-", "éa"] {
+        assert_eq!(try_parse_input(text), Err(MoveError::BadInput { text: text.to_string() }), "{:?}", text);
+    }
+
+    // The Option version agrees
+    for text in ["cd", "ai", "c4", ""] {
+        assert_eq!(parse_input(text), try_parse_input(text).ok());
+    }
+}
+
+// Test each reason a move on the board can be refused
+#[test]
+fn test_check_move() {
+    let board = create_initial_board();
+    assert_eq!(check_move(&board, 'B', 2, 3), Ok(()));
+    assert_eq!(check_move(&board, 'B', 3, 3), Err(MoveError::Occupied { row: 3, col: 3 }));
+    assert_eq!(check_move(&board, 'B', 0, 0), Err(MoveError::NoFlips { row: 0, col: 0 }));
+    assert_eq!(check_move(&board, 'B', 8, 0), Err(MoveError::OffBoard { row: 8, col: 0 }));
+    assert_eq!(check_move(&board, 'X', 2, 3), Err(MoveError::BadPlayer { found: 'X' }));
+    // c4 flips for Black but not for White
+    assert_eq!(check_move(&board, 'W', 2, 3), Err(MoveError::NoFlips { row: 2, col: 3 }));
+
+    for (row, col) in [(2, 3), (3, 3), (0, 0), (8, 0)] {
+        assert_eq!(is_valid_move(&board, 'B', row, col), check_move(&board, 'B', row, col).is_ok());
+    }
+}
+
+// Test that try_apply_move refuses what apply_move would place anyway
+#[test]
+fn test_try_apply_move() {
+    let mut board = create_initial_board();
+    assert_eq!(try_apply_move(&mut board, 'B', 0, 0), Err(MoveError::NoFlips { row: 0, col: 0 }));
+    assert_eq!(board, create_initial_board());
+
+    let mut forced = create_initial_board();
+    apply_move(&mut forced, 'B', 0, 0);
+    assert_eq!(forced[0][0], 'B');
+
+    assert_eq!(try_apply_move(&mut board, 'B', 2, 3), Ok(()));
+    let mut expected = create_initial_board();
+    apply_move(&mut expected, 'B', 2, 3);
+    assert_eq!(board, expected);
+    assert_eq!(try_apply_move(&mut board, 'W', 2, 3), Err(MoveError::Occupied { row: 2, col: 3 }));
+    assert_eq!(board, expected);
+}
+
+// Test that games and histories report why a move was refused
+#[test]
+fn test_try_play() {
+    let mut game = Game::new();
+    assert_eq!(game.try_play(3, 4), Err(MoveError::Occupied { row: 3, col: 4 }));
+    assert_eq!(game.try_play(9, 9), Err(MoveError::OffBoard { row: 9, col: 9 }));
+    assert_eq!(game, Game::new());
+    assert_eq!(game.try_play(2, 3), Ok(()));
+    assert_eq!(game.to_move(), Player::White);
+    assert_eq!(game.check_move(0, 0), Err(MoveError::NoFlips { row: 0, col: 0 }));
+
+    let mut history = History::new();
+    assert_eq!(history.try_play(0, 0), Err(MoveError::NoFlips { row: 0, col: 0 }));
+    assert!(!history.can_undo());
+    assert_eq!(history.try_play(2, 3), Ok(()));
+    assert_eq!(history.game(), &game);
+}
+
+// Test the messages shown for each reason
+#[test]
+fn test_move_error_messages() {
+    let cases = [
+        (MoveError::BadInput { text: "c4".to_string() }, "'c4' is not a move: enter a row letter then a column letter, like cd"),
+        (MoveError::BadPlayer { found: 'X' }, "'X' is not a player, expected 'B' or 'W'"),
+        (MoveError::OffBoard { row: 0, col: 8 }, "ai is off the board"),
+        (MoveError::Occupied { row: 3, col: 3 }, "dd is already taken"),
+        (MoveError::Blocked { row: 2, col: 2 }, "cc is blocked"),
+        (MoveError::NoFlips { row: 0, col: 0 }, "aa does not flip any discs"),
+    ];
+    for (error, message) in cases {
+        assert_eq!(error.to_string(), message);
+    }
+}
@@ -0,0 +1,47 @@
+// Import the move listing helpers
+use reversi::game::{flip_count, grid_from_board};
+use reversi::{create_initial_board, valid_moves, Game, LegalMove, Player};
+
+
+// Test the four opening moves, each flipping one disc
+#[test]
+fn test_opening_moves() {
+    let expected = vec![
+        LegalMove { row: 2, col: 3, flips: 1 },
+        LegalMove { row: 3, col: 2, flips: 1 },
+        LegalMove { row: 4, col: 5, flips: 1 },
+        LegalMove { row: 5, col: 4, flips: 1 },
+    ];
+    assert_eq!(Game::new().list_moves(), expected);
+}
+
+// Test that flip counts cover every direction and are zero for illegal or
+// occupied squares
+#[test]
+fn test_flip_count() {
+    let game = Game::from_position(
+        "........ ........ ........ .WWWB... W....... B....... ........ ........ B",
+    )
+    .unwrap();
+    let grid = game.grid();
+    assert_eq!(flip_count(grid, Player::Black, 3, 0), 4);
+    assert_eq!(flip_count(grid, Player::White, 3, 0), 0);
+    assert_eq!(flip_count(grid, Player::Black, 3, 1), 0);
+    assert_eq!(flip_count(grid, Player::Black, 0, 0), 0);
+    assert_eq!(flip_count(grid, Player::Black, 9, 9), 0);
+    for mv in game.list_moves() {
+        assert_eq!(flip_count(grid, Player::Black, mv.row, mv.col), mv.flips);
+        assert!(game.is_legal(mv.row, mv.col));
+    }
+    assert_eq!(game.list_moves().len(), game.legal_moves().len());
+}
+
+// Test the char-board wrapper, including an unknown player
+#[test]
+fn test_valid_moves() {
+    let board = create_initial_board();
+    assert_eq!(valid_moves(&board, 'W'), vec![(2, 4, 1), (3, 5, 1), (4, 2, 1), (5, 3, 1)]);
+    assert!(valid_moves(&board, 'X').is_empty());
+    let grid = grid_from_board(&board);
+    assert_eq!(flip_count(&grid, Player::White, 2, 4), 1);
+}
@@ -0,0 +1,172 @@
+// Import the NBoard protocol adapter and drive it like a GUI would
+use reversi::nboard::{self, move_text, parse_ggf, parse_move, Command, Session, UNITS_PER_DISC};
+use reversi::{Bitboard, Engine, Game, Heuristic, History, Limit, Move, Player};
+
+
+// Run a session over the given input and return what it wrote to stdout and
+// stderr
+fn talk(input: &str) -> (String, String) {
+    let mut out = Vec::new();
+    let mut errors = Vec::new();
+    nboard::run(input.as_bytes(), &mut out, &mut errors, 2).unwrap();
+    (String::from_utf8(out).unwrap(), String::from_utf8(errors).unwrap())
+}
+
+// Test that commands are parsed and survive a round trip through their text
+#[test]
+fn test_parse_commands() {
+    let commands = [
+        Command::NBoard(2),
+        Command::SetDepth(12),
+        Command::SetGame("(;GM[Othello];)".to_string()),
+        Command::SetContempt,
+        Command::Move(Move::Play(4, 5)),
+        Command::Move(Move::Pass),
+        Command::Go,
+        Command::Hint(3),
+        Command::Ping("7".to_string()),
+        Command::Learn,
+        Command::Quit,
+    ];
+    for command in commands {
+        assert_eq!(Command::parse(&command.to_string()), Ok(command.clone()), "{}", command);
+    }
+    assert_eq!(Command::parse("move f5/-2.00/1.5"), Ok(Command::Move(Move::Play(4, 5))));
+    assert_eq!(Command::parse("move pa"), Ok(Command::Move(Move::Pass)));
+
+    assert_eq!(Command::parse("genmove"), Err("unknown command 'genmove'".to_string()));
+    assert_eq!(Command::parse("set depth 0"), Err("depth must be at least 1".to_string()));
+    assert_eq!(Command::parse("set depth x"), Err("bad depth 'x'".to_string()));
+    assert_eq!(Command::parse("set colour black"), Err("unknown setting 'colour'".to_string()));
+    assert_eq!(Command::parse("move Z9"), Err("bad move 'Z9'".to_string()));
+    assert_eq!(Command::parse("hint"), Err("missing hint count".to_string()));
+}
+
+// Test the NBoard move names
+#[test]
+fn test_move_text() {
+    assert_eq!(move_text(Move::Play(4, 5)), "F5");
+    assert_eq!(move_text(Move::Play(0, 0)), "A1");
+    assert_eq!(move_text(Move::Pass), "PA");
+    assert_eq!(parse_move("h8"), Ok(Move::Play(7, 7)));
+    assert_eq!(parse_move("PA//0.1"), Ok(Move::Pass));
+}
+
+// Test that GGF games are read with their starting position and moves
+#[test]
+fn test_parse_ggf() {
+    let ggf = "(;GM[Othello]PC[NBoard]PB[me]PW[you]RE[?]TI[15:00//02:00]TY[8]\
+               BO[8 ---------------------------O*------*O--------------------------- *]\
+               B[F5//0.29]W[F6]B[E6]W[F4/-2.00/10.5];)";
+    let history = parse_ggf(ggf).unwrap();
+    assert_eq!(history, History::from_transcript("f5f6e6f4").unwrap());
+
+    // Rows may be split into words, and the board may be any position
+    let ggf = "(;GM[Othello]BO[8 -------- -------- -------- ---O*--- ---*O--- -------- -------- -------- O];)";
+    let history = parse_ggf(ggf).unwrap();
+    assert_eq!(history.game().to_move(), Player::White);
+    assert_eq!(history.game().score(), (2, 2));
+
+    assert_eq!(parse_ggf("(;GM[Othello];)").unwrap(), History::new());
+    assert!(parse_ggf("GM[Othello]").is_err());
+    assert!(parse_ggf("(;BO[10 - *];)").unwrap_err().contains("8x8"));
+    assert_eq!(parse_ggf("(;W[F5];)").unwrap_err(), "move 1: it is not White's turn");
+    assert_eq!(parse_ggf("(;B[A1];)").unwrap_err(), "move 1: A1 is not a legal move");
+}
+
+// Test the handshake, ping and learn answers
+#[test]
+fn test_handshake() {
+    let (out, errors) = talk("nboard 2\nset depth 4\nset contempt 0\nping 1\nlearn\nquit\nping 2\n");
+    assert_eq!(out, "set myname reversi\npong 1\nlearned\n");
+    assert_eq!(errors, "");
+}
+
+// Test that "go" answers with a legal move without playing it
+#[test]
+fn test_go() {
+    let mut session = Session::new(2);
+    let mut out = Vec::new();
+    assert_eq!(session.handle(Command::Move(Move::Play(4, 5)), &mut out).unwrap(), Ok(true));
+    assert_eq!(session.handle(Command::Go, &mut out).unwrap(), Ok(true));
+    let out = String::from_utf8(out).unwrap();
+    let answer = out.lines().find_map(|line| line.strip_prefix("=== ")).unwrap();
+    let (row, col) = match parse_move(answer).unwrap() {
+        Move::Play(row, col) => (row, col),
+        Move::Pass => panic!("passed with legal moves"),
+    };
+    assert!(session.history().game().is_legal(row, col));
+    assert_eq!(session.history().moves(), [Move::Play(4, 5)]);
+    assert_eq!(answer.split('/').count(), 3);
+}
+
+// Test that evaluations are disc margins: exact once the game is decided,
+// and the heuristic score scaled down to discs before that
+#[test]
+fn test_go_evaluations() {
+    let ggf = "(;GM[Othello]BO[8 \
+               ******** ******** ******** ******** ******** ******** ******O- -------- *];)";
+    let (out, _) = talk(&format!("set game {}\ngo\n", ggf));
+    assert!(out.contains("=== H7/+56/"), "{}", out);
+
+    let (out, _) = talk("move F5\ngo\n");
+    let answer = out.lines().find_map(|line| line.strip_prefix("=== ")).unwrap();
+    let eval: i32 = answer.split('/').nth(1).unwrap().parse().unwrap();
+    let game = History::from_transcript("f5").unwrap().game().clone();
+    let score = Engine::new(Heuristic::default(), Limit::Depth(2)).search(&Bitboard::from_grid(game.grid()), game.to_move()).score;
+    assert_eq!(eval, (score as f64 / UNITS_PER_DISC as f64).round() as i32);
+    assert!(eval.abs() < 64, "{}", answer);
+}
+
+// Test that "go" passes when the side to move has no legal move
+#[test]
+fn test_go_pass() {
+    let ggf = "(;GM[Othello]BO[8 \
+               ******** ******** ******** ******** ******** ******** ******O- -------- O];)";
+    let history = parse_ggf(ggf).unwrap();
+    assert!(history.game().must_pass());
+    let (out, _) = talk(&format!("set game {}\ngo\n", ggf));
+    assert!(out.contains("=== PA/"), "{}", out);
+}
+
+// Test that hints list the requested number of moves, best first
+#[test]
+fn test_hint() {
+    let (out, errors) = talk("hint 2\n");
+    assert_eq!(errors, "");
+    let searches: Vec<&str> = out.lines().filter(|line| line.starts_with("search ")).collect();
+    // Two lines for each of the two depths searched
+    assert_eq!(searches.len(), 4);
+    for line in searches {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        assert_eq!(words.len(), 5, "{}", line);
+        assert!(["C4", "D3", "E6", "F5"].contains(&&words[1][..2]), "{}", line);
+        assert_eq!(words[3], "0");
+    }
+    assert_eq!(out.lines().last(), Some("status"));
+}
+
+// Test that rejected commands are reported and leave the game unchanged
+#[test]
+fn test_errors() {
+    let (out, errors) = talk("move A1\nmove PA\nbogus\nset game (;B[A1];)\nmove F5\nping 1\n");
+    assert_eq!(out, "pong 1\n");
+    let errors: Vec<&str> = errors.lines().collect();
+    assert_eq!(
+        errors,
+        [
+            "move A1: A1 is not a legal move",
+            "move PA: Black has a legal move and cannot pass",
+            "bogus: unknown command 'bogus'",
+            "set game (;B[A1];): move 1: A1 is not a legal move",
+        ]
+    );
+
+    let mut session = Session::new(2);
+    let mut out = Vec::new();
+    session.handle(Command::Move(Move::Play(4, 5)), &mut out).unwrap().unwrap();
+    assert!(session.handle(Command::Move(Move::Play(4, 5)), &mut out).unwrap().is_err());
+    let mut expected = Game::new();
+    expected.play(4, 5);
+    assert_eq!(session.history().game(), &expected);
+}
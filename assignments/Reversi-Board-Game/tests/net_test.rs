@@ -0,0 +1,152 @@
+// Import the TCP protocol and play games over localhost
+use std::net::TcpListener;
+use std::thread;
+
+use reversi::net::{self, Client, ClientMessage, Outcome, ServerMessage};
+use reversi::{Game, Player};
+
+
+// Start a server on a free local port and join it with two clients
+fn start_game() -> (thread::JoinHandle<Outcome>, Client, Client) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || net::serve(&listener).unwrap());
+
+    let mut black = Client::connect(addr).unwrap();
+    black.send(&ClientMessage::Join("alice".to_string())).unwrap();
+    assert_eq!(black.recv().unwrap(), ServerMessage::Welcome(Player::Black));
+    let mut white = Client::connect(addr).unwrap();
+    white.send(&ClientMessage::Join("bob".to_string())).unwrap();
+    assert_eq!(white.recv().unwrap(), ServerMessage::Welcome(Player::White));
+
+    let start = ServerMessage::Board(Game::new().to_position());
+    for client in [&mut black, &mut white] {
+        assert_eq!(client.recv().unwrap(), ServerMessage::Start);
+        assert_eq!(client.recv().unwrap(), start);
+    }
+    (server, black, white)
+}
+
+// Test that every message survives a round trip through its text form
+#[test]
+fn test_message_round_trip() {
+    let client = [
+        ClientMessage::Join("two words".to_string()),
+        ClientMessage::Move(2, 3),
+        ClientMessage::Pass,
+        ClientMessage::Board,
+        ClientMessage::Quit,
+    ];
+    for message in client {
+        assert_eq!(ClientMessage::parse(&message.to_string()), Ok(message));
+    }
+    let server = [
+        ServerMessage::Welcome(Player::White),
+        ServerMessage::Start,
+        ServerMessage::Board(Game::new().to_position()),
+        ServerMessage::Moved(Player::Black, 2, 3),
+        ServerMessage::Passed(Player::White),
+        ServerMessage::Error("not your turn".to_string()),
+        ServerMessage::Left(Player::Black),
+        ServerMessage::GameOver(40, 24),
+    ];
+    for message in server {
+        assert_eq!(ServerMessage::parse(&message.to_string()), Ok(message));
+    }
+    assert!(ClientMessage::parse("MOVE zz").is_err());
+    assert!(ClientMessage::parse("DANCE").is_err());
+}
+
+// Test that clients which leave, talk nonsense or send overlong lines before
+// joining do not stop the server from seating the players that follow
+#[test]
+fn test_failed_joins_are_skipped() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || net::serve(&listener).unwrap());
+
+    drop(Client::connect(addr).unwrap());
+    let mut rude = Client::connect(addr).unwrap();
+    rude.send(&ClientMessage::Board).unwrap();
+    assert_eq!(rude.recv().unwrap(), ServerMessage::Error("expected JOIN <name>".to_string()));
+    let mut chatty = Client::connect(addr).unwrap();
+    chatty.send(&ClientMessage::Join("x".repeat(1000))).unwrap();
+    assert_eq!(chatty.recv().unwrap(), ServerMessage::Error("expected JOIN <name>".to_string()));
+
+    let mut black = Client::connect(addr).unwrap();
+    black.send(&ClientMessage::Join("alice".to_string())).unwrap();
+    assert_eq!(black.recv().unwrap(), ServerMessage::Welcome(Player::Black));
+    let mut white = Client::connect(addr).unwrap();
+    white.send(&ClientMessage::Join("bob".to_string())).unwrap();
+    assert_eq!(white.recv().unwrap(), ServerMessage::Welcome(Player::White));
+    assert_eq!(black.recv().unwrap(), ServerMessage::Start);
+
+    black.send(&ClientMessage::Quit).unwrap();
+    assert_eq!(server.join().unwrap(), Outcome::Abandoned(Player::Black));
+}
+
+// Test that the server rejects out-of-turn, illegal and pointless moves
+#[test]
+fn test_server_rejects_bad_moves() {
+    let (server, mut black, mut white) = start_game();
+
+    white.send(&ClientMessage::Move(2, 4)).unwrap();
+    assert_eq!(white.recv().unwrap(), ServerMessage::Error("not your turn".to_string()));
+    black.send(&ClientMessage::Move(0, 0)).unwrap();
+    assert_eq!(black.recv().unwrap(), ServerMessage::Error("illegal move aa: aa does not flip any discs".to_string()));
+    black.send(&ClientMessage::Pass).unwrap();
+    assert_eq!(black.recv().unwrap(), ServerMessage::Error("you have a legal move".to_string()));
+
+    black.send(&ClientMessage::Move(2, 3)).unwrap();
+    let mut expected = Game::new();
+    expected.play(2, 3);
+    for client in [&mut black, &mut white] {
+        assert_eq!(client.recv().unwrap(), ServerMessage::Moved(Player::Black, 2, 3));
+        assert_eq!(client.recv().unwrap(), ServerMessage::Board(expected.to_position()));
+    }
+
+    white.send(&ClientMessage::Board).unwrap();
+    assert_eq!(white.recv().unwrap(), ServerMessage::Board(expected.to_position()));
+
+    white.send(&ClientMessage::Quit).unwrap();
+    assert_eq!(black.recv().unwrap(), ServerMessage::Left(Player::White));
+    assert_eq!(server.join().unwrap(), Outcome::Abandoned(Player::White));
+}
+
+// Test a complete game played by two clients that always take the first
+// legal move, passing when they have to
+#[test]
+fn test_full_game() {
+    let (server, black, white) = start_game();
+    let mut clients = [black, white];
+    let mut game = Game::new();
+
+    loop {
+        let mover = if game.to_move() == Player::Black { 0 } else { 1 };
+        let event = match game.legal_moves().first() {
+            Some(&(row, col)) => {
+                clients[mover].send(&ClientMessage::Move(row, col)).unwrap();
+                game.play(row, col);
+                ServerMessage::Moved(game.to_move().opponent(), row, col)
+            }
+            None => {
+                clients[mover].send(&ClientMessage::Pass).unwrap();
+                game.pass();
+                ServerMessage::Passed(game.to_move().opponent())
+            }
+        };
+        for client in clients.iter_mut() {
+            assert_eq!(client.recv().unwrap(), event);
+            assert_eq!(client.recv().unwrap(), ServerMessage::Board(game.to_position()));
+        }
+        if game.is_over() {
+            break;
+        }
+    }
+
+    let (black, white) = game.score();
+    for client in clients.iter_mut() {
+        assert_eq!(client.recv().unwrap(), ServerMessage::GameOver(black, white));
+    }
+    assert_eq!(server.join().unwrap(), Outcome::Finished(black, white));
+}
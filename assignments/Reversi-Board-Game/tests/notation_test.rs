@@ -0,0 +1,50 @@
+// Import the position notation parser and serializer from the lib.rs module
+use reversi::notation::{initial_position, parse_position_sized};
+use reversi::{create_initial_board, format_position, parse_position, Game, Player, PositionError};
+
+const START: &str = "...........................WB......BW........................... B";
+
+
+// Test that the initial position is written as expected
+#[test]
+fn test_format_initial_position() {
+    assert_eq!(initial_position(), START);
+    assert_eq!(format_position(&create_initial_board(), Player::Black), START);
+    assert_eq!(Game::new().to_position(), START);
+}
+
+// Test parsing, including whitespace between rows
+#[test]
+fn test_parse_position() {
+    assert_eq!(parse_position(START), Ok((create_initial_board(), Player::Black)));
+
+    let rows = "........\n........\n........\n...WB...\n...BW...\n........\n........\n........\nW\n";
+    assert_eq!(parse_position(rows), Ok((create_initial_board(), Player::White)));
+
+    let game = Game::from_position(START).unwrap();
+    assert_eq!(game, Game::new());
+}
+
+// Test round trips on another board size
+#[test]
+fn test_sized_round_trip() {
+    let game = Game::<6>::new_sized();
+    let text = game.to_position();
+    assert_eq!(text.len(), 36 + 2);
+    assert_eq!(Game::<6>::from_position_sized(&text), Ok(game));
+    assert_eq!(parse_position_sized::<6>(START), Err(PositionError::TooLong { expected: 37, found: 65 }));
+}
+
+// Test that malformed input explains what is wrong
+#[test]
+fn test_position_errors() {
+    assert_eq!(parse_position("BW. B"), Err(PositionError::TooShort { expected: 64, found: 4 }));
+    assert_eq!(parse_position(&START[..64]), Err(PositionError::MissingSide));
+    assert_eq!(parse_position(&format!("{}W", START)), Err(PositionError::TooLong { expected: 65, found: 66 }));
+    assert_eq!(parse_position(&START.replace(" B", " x")), Err(PositionError::BadSide { found: 'x' }));
+
+    let bad = format!("{}X{}", &START[..10], &START[11..]);
+    let err = parse_position(&bad).unwrap_err();
+    assert_eq!(err, PositionError::BadSquare { index: 10, name: "bc".to_string(), found: 'X' });
+    assert_eq!(err.to_string(), "square 10 (bc) is 'X', expected 'B', 'W', '.' or '#'");
+}
@@ -0,0 +1,82 @@
+// Import the perft counters and check them against the known counts
+use reversi::perft::{divide, perft, perft_board, START_COUNTS};
+use reversi::{create_initial_board, create_initial_board_sized, parse_position, Bitboard, Game, Move, Player};
+
+
+// Count leaves with the typed game API, as a third implementation
+fn perft_game<const N: usize>(game: &Game<N>, depth: u32) -> u64 {
+    if depth == 0 || game.is_over() {
+        return 1;
+    }
+    if game.must_pass() {
+        let mut child = game.clone();
+        child.pass();
+        return perft_game(&child, depth - 1);
+    }
+    game.legal_moves()
+        .into_iter()
+        .map(|(row, col)| {
+            let mut child = game.clone();
+            child.play(row, col);
+            perft_game(&child, depth - 1)
+        })
+        .sum()
+}
+
+// Test the bitboard counts from the standard start
+#[test]
+fn test_bitboard_start_counts() {
+    let board = Bitboard::initial();
+    for (depth, &expected) in (1..=8).zip(START_COUNTS.iter()) {
+        assert_eq!(perft(&board, Player::Black, depth), expected, "depth {}", depth);
+    }
+}
+
+// Test the character board functions against the same counts
+#[test]
+fn test_board_start_counts() {
+    let board = create_initial_board();
+    for (depth, &expected) in (1..=6).zip(START_COUNTS.iter()) {
+        assert_eq!(perft_board(&board, 'B', depth), expected, "depth {}", depth);
+    }
+}
+
+// Test that the character board and typed game agree on a smaller board
+#[test]
+fn test_small_board_counts() {
+    let board = create_initial_board_sized::<6>();
+    let game = Game::<6>::new_sized();
+    for depth in 1..=7 {
+        assert_eq!(perft_board(&board, 'B', depth), perft_game(&game, depth), "depth {}", depth);
+    }
+}
+
+// Test that passes use up a ply and finished games are single leaves
+#[test]
+fn test_passes_and_game_end() {
+    // White has no move here, but Black can take either corner square
+    let (board, _) = parse_position(
+        "BBBBBBBB BBBBBBBB BBBBBBBB BBBBBBBB BBBBBBBB BBBBBBBB BBBBBBWW BBBBBB.. W",
+    )
+    .unwrap();
+    let bitboard = Bitboard::from_board(&board);
+    assert_eq!(divide(&bitboard, Player::White, 2), vec![(Move::Pass, 2)]);
+    assert_eq!(perft(&bitboard, Player::White, 1), 1);
+    for depth in 1..=5 {
+        assert_eq!(perft(&bitboard, Player::White, depth), perft_board(&board, 'W', depth));
+    }
+
+    let (full, player) = parse_position(&format!("{} B", "B".repeat(64))).unwrap();
+    assert_eq!(perft(&Bitboard::from_board(&full), player, 5), 1);
+    assert_eq!(perft_board(&full, 'B', 5), 1);
+    assert!(divide(&Bitboard::from_board(&full), player, 5).is_empty());
+}
+
+// Test that the counts below each root move add up to the total
+#[test]
+fn test_divide_sums_to_total() {
+    let board = Bitboard::initial();
+    let split = divide(&board, Player::Black, 5);
+    assert_eq!(split.len(), 4);
+    assert_eq!(split.iter().map(|&(_, count)| count).sum::<u64>(), START_COUNTS[4]);
+}
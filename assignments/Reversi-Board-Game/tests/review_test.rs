@@ -0,0 +1,134 @@
+// Import the game review and judge some games
+use reversi::review::{annotated_transcript, review, tally, MoveReview, Verdict, BLUNDER, INACCURACY, MISTAKE};
+use reversi::{Bitboard, Engine, Game, Heuristic, History, Limit, Move, Player};
+
+
+// The engine that plays and reviews the games, shallow so the tests are quick
+fn engine() -> Engine {
+    Engine::new(Heuristic::default(), Limit::Depth(2))
+}
+
+// Play up to `plies` moves from `start`, each time taking the move at `pick`
+// in the engine's ranking (0 for the best, usize::MAX for the worst)
+fn play_ranked_from(start: Game, plies: usize, pick: usize) -> History {
+    let engine = engine();
+    let mut history = History::from_game(start);
+    for _ in 0..plies {
+        let game = history.game();
+        if game.is_over() {
+            break;
+        }
+        if game.must_pass() {
+            history.pass();
+            continue;
+        }
+        let analysis = engine.analyze(&Bitboard::from_grid(game.grid()), game.to_move(), |_| {});
+        let variation = &analysis.variations[pick.min(analysis.variations.len() - 1)];
+        history.apply(variation.moves[0]);
+    }
+    history
+}
+
+// `play_ranked_from` the standard start
+fn play_ranked(plies: usize, pick: usize) -> History {
+    play_ranked_from(Game::new(), plies, pick)
+}
+
+// A review of f5 with the given scores
+fn reviewed(number: usize, player: Player, played_score: i32, best_score: i32) -> MoveReview {
+    MoveReview { number, player, played: (4, 5), played_score, best: (2, 3), best_score, choices: 4 }
+}
+
+// Test the losses at which each verdict starts
+#[test]
+fn test_verdicts() {
+    assert_eq!(Verdict::from_loss(0), Verdict::Best);
+    assert_eq!(Verdict::from_loss(1), Verdict::Good);
+    assert_eq!(Verdict::from_loss(INACCURACY - 1), Verdict::Good);
+    assert_eq!(Verdict::from_loss(INACCURACY), Verdict::Inaccuracy);
+    assert_eq!(Verdict::from_loss(MISTAKE), Verdict::Mistake);
+    assert_eq!(Verdict::from_loss(BLUNDER), Verdict::Blunder);
+    assert_eq!(Verdict::from_loss(i32::MAX), Verdict::Blunder);
+    assert_eq!(Verdict::Blunder.glyph(), "??");
+    assert_eq!(Verdict::Mistake.to_string(), "mistake");
+
+    let review = reviewed(1, Player::Black, -40, 15);
+    assert_eq!(review.loss(), 55);
+    assert_eq!(review.verdict(), Verdict::Mistake);
+}
+
+// Test that a game of the engine's own choices has nothing to flag
+#[test]
+fn test_best_moves() {
+    let history = play_ranked(12, 0);
+    let reviews = review(&history, &engine(), |_, _| {});
+    assert_eq!(reviews.len(), 12);
+    for (i, review) in reviews.iter().enumerate() {
+        assert_eq!(review.number, i + 1);
+        assert_eq!(review.loss(), 0, "move {}", review.number);
+        assert_eq!(review.verdict(), Verdict::Best);
+    }
+    assert_eq!(tally(&reviews, Player::Black), [6, 0, 0, 0, 0]);
+    assert!(!annotated_transcript(&reviews).contains('?'));
+}
+
+// Test that the worst moves are scored as the engine ranks them and flagged
+#[test]
+fn test_worst_moves() {
+    let history = play_ranked(12, usize::MAX);
+    let mut calls = Vec::new();
+    let reviews = review(&history, &engine(), |done, total| calls.push((done, total)));
+    assert_eq!(calls.last(), Some(&(12, 12)));
+
+    let mut game = history.start().clone();
+    for (review, &mv) in reviews.iter().zip(history.moves()) {
+        let analysis = engine().analyze(&Bitboard::from_grid(game.grid()), game.to_move(), |_| {});
+        let worst = analysis.variations.last().unwrap();
+        assert_eq!(Move::Play(review.played.0, review.played.1), mv);
+        assert_eq!(review.player, game.to_move());
+        assert_eq!(review.choices, analysis.variations.len());
+        if review.choices > 1 {
+            assert_eq!(review.played_score, worst.score);
+            assert_eq!(review.best_score, analysis.variations[0].score);
+        }
+        game.play(review.played.0, review.played.1);
+    }
+
+    let flagged = reviews.iter().filter(|review| review.verdict() >= Verdict::Inaccuracy).count();
+    assert!(flagged > 0);
+    let counts: usize = [Player::Black, Player::White].iter().map(|&player| tally(&reviews, player)[2..].iter().sum::<usize>()).sum();
+    assert_eq!(counts, flagged);
+}
+
+// Test that passes are skipped and moves without an alternative are judged
+// best
+#[test]
+fn test_passes_and_forced_moves() {
+    let start = Game::from_position("WW.BBBBBWWWWBBB.WWWBBBB.WWWWWBBWWWWWBW.BBWWBBWBWBWWWWBWW.BBBBWWW W").unwrap();
+    let history = play_ranked_from(start, 20, 0);
+    assert!(history.game().is_over());
+    assert!(history.moves().contains(&Move::Pass));
+
+    let reviews = review(&history, &engine(), |_, _| {});
+    assert_eq!(reviews.len(), history.moves().iter().filter(|&&mv| mv != Move::Pass).count());
+    assert!(reviews.iter().any(|review| review.choices == 1));
+    for review in reviews.iter().filter(|review| review.choices == 1) {
+        assert_eq!(review.best, review.played);
+        assert_eq!(review.verdict(), Verdict::Best);
+    }
+}
+
+// Test the annotated transcript and the tally of verdicts
+#[test]
+fn test_annotations() {
+    let reviews = [
+        reviewed(1, Player::Black, 10, 10),
+        reviewed(2, Player::White, 0, 25),
+        reviewed(3, Player::Black, -60, 10),
+        reviewed(4, Player::White, -200, 0),
+        reviewed(5, Player::Black, 5, 9),
+    ];
+    assert_eq!(annotated_transcript(&reviews), "f5 f5?! f5? f5?? f5");
+    assert_eq!(tally(&reviews, Player::Black), [1, 1, 0, 1, 0]);
+    assert_eq!(tally(&reviews, Player::White), [0, 0, 1, 0, 1]);
+}
@@ -0,0 +1,180 @@
+// Import the rule variants: goals, starting setups and blocked squares
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use reversi::perft::{perft, perft_board};
+use reversi::rules::{parse_blocked, RulesError, RANDOM_PLIES};
+use reversi::{Bitboard, Cell, Engine, Game, Goal, Heuristic, Limit, MoveError, Player, PositionError, Rules, Setup, Solver};
+
+
+fn rng() -> StdRng {
+    StdRng::seed_from_u64(5)
+}
+
+// Test that the goal decides who wins with the same disc counts
+#[test]
+fn test_goal_winner() {
+    assert_eq!(Goal::MostDiscs.winner(40, 24), Some(Player::Black));
+    assert_eq!(Goal::MostDiscs.winner(24, 40), Some(Player::White));
+    assert_eq!(Goal::FewestDiscs.winner(40, 24), Some(Player::White));
+    assert_eq!(Goal::FewestDiscs.winner(24, 40), Some(Player::Black));
+    assert_eq!(Goal::FewestDiscs.winner(32, 32), None);
+
+    assert_eq!(Goal::parse("anti"), Ok(Goal::FewestDiscs));
+    assert_eq!(Goal::parse("standard"), Ok(Goal::MostDiscs));
+    assert_eq!(Goal::FewestDiscs.to_string(), "anti");
+    assert_eq!(Goal::parse("losing"), Err(RulesError::BadGoal { text: "losing".to_string() }));
+}
+
+// Test that a game reports the winner for its own goal
+#[test]
+fn test_game_goal() {
+    let position = "BBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBWWWW B";
+    let game = Game::from_position(position).unwrap();
+    assert!(game.is_over());
+    assert_eq!(game.goal(), Goal::MostDiscs);
+    assert_eq!(game.winner(), Some(Player::Black));
+
+    let anti = game.with_goal(Goal::FewestDiscs);
+    assert_eq!(anti.winner(), Some(Player::White));
+    // The goal carries over to the positions after moves
+    let mut game = Game::new().with_goal(Goal::FewestDiscs);
+    game.play(2, 3);
+    assert_eq!(game.goal(), Goal::FewestDiscs);
+}
+
+// Test reading starting setups and blocked square lists
+#[test]
+fn test_parse_setup_and_blocked() {
+    assert_eq!(Setup::parse("standard"), Ok(Setup::Standard));
+    assert_eq!(Setup::parse("random"), Ok(Setup::Random { plies: RANDOM_PLIES }));
+    assert_eq!(Setup::parse("random:10"), Ok(Setup::Random { plies: 10 }));
+    for text in ["", "random:", "random:x", "standard:1", "crossed"] {
+        assert_eq!(Setup::parse(text), Err(RulesError::BadSetup { text: text.to_string() }), "{:?}", text);
+    }
+
+    assert_eq!(parse_blocked("aa, hh bc", 8), Ok(vec![(0, 0), (7, 7), (1, 2)]));
+    assert_eq!(parse_blocked("", 8), Ok(Vec::new()));
+    assert_eq!(parse_blocked("aa,ii", 8), Err(RulesError::BadSquare(MoveError::OffBoard { row: 8, col: 8 })));
+    assert_eq!(parse_blocked("ii", 10), Ok(vec![(8, 8)]));
+}
+
+// Test that the standard rules start the usual game
+#[test]
+fn test_standard_start() {
+    let rules = Rules::standard();
+    assert!(rules.has_standard_play());
+    assert_eq!(rules.start::<8>(&mut rng()), Ok(Game::new()));
+    assert_eq!(rules.start::<6>(&mut rng()), Ok(Game::new_sized()));
+}
+
+// Test that blocked squares can never be played and stop lines of discs
+#[test]
+fn test_blocked_squares() {
+    let rules = Rules { blocked: vec![(2, 3), (5, 4)], ..Rules::standard() };
+    assert!(!rules.has_standard_play());
+    let mut game: Game = rules.start(&mut rng()).unwrap();
+    assert_eq!(game.cell(2, 3), Cell::Blocked);
+    assert_eq!(game.board()[2][3], '#');
+    assert_eq!(game.check_move(2, 3), Err(MoveError::Blocked { row: 2, col: 3 }));
+    // Two of Black's four opening moves are gone
+    assert_eq!(game.legal_moves(), vec![(3, 2), (4, 5)]);
+    assert!(!game.play(5, 4));
+
+    game.play(3, 2);
+    assert!(game.legal_moves().iter().all(|&(row, col)| game.cell(row, col) == Cell::Empty));
+    assert_eq!(game.score(), (4, 1));
+
+    // Blocked squares are not discs
+    assert_eq!(game.grid().iter().flatten().filter(|&&cell| cell == Cell::Blocked).count(), 2);
+}
+
+// Test that a blocked square cuts off a line that would otherwise flip
+#[test]
+fn test_blocked_square_breaks_a_line() {
+    // Black at aa, White at ab, ac; Black may play ad. With ac blocked the
+    // white disc at ab is no longer between aa and ad.
+    let position = format!("BWW.{} B", ".".repeat(60));
+    let rules = Rules { setup: Setup::Position(position.clone()), ..Rules::standard() };
+    let game: Game = rules.start(&mut rng()).unwrap();
+    assert!(game.is_legal(0, 3));
+
+    let blocked = format!("BW#.{} B", ".".repeat(60));
+    let rules = Rules { setup: Setup::Position(blocked), ..Rules::standard() };
+    assert!(!rules.has_standard_play());
+    let game: Game = rules.start(&mut rng()).unwrap();
+    assert_eq!(game.cell(0, 2), Cell::Blocked);
+    assert_eq!(game.check_move(0, 3), Err(MoveError::NoFlips { row: 0, col: 3 }));
+}
+
+// Test that the bitboard search tools never play on a blocked square
+#[test]
+fn test_blocked_squares_in_bitboards() {
+    let rules = Rules { blocked: vec![(2, 3), (5, 4)], ..Rules::standard() };
+    let game: Game = rules.start(&mut rng()).unwrap();
+    let board = Bitboard::from_grid(game.grid());
+    assert_eq!(board.blocked, 1 << 19 | 1 << 44);
+    assert_eq!(board.to_grid(), *game.grid());
+    assert_eq!(board.legal_moves(Player::Black), 1 << 26 | 1 << 37);
+    assert_eq!(board.empty().count_ones(), 58);
+    assert_eq!(perft(&board, Player::Black, 1), 2);
+    assert_eq!(perft(&board, Player::Black, 4), perft_board(&game.board(), 'B', 4));
+
+    // Black's only move, next to its disc at aa, is blocked, so the game is
+    // over as a draw
+    let game: Game = Game::from_position(&format!("BW#{} B", ".".repeat(61))).unwrap();
+    let board = Bitboard::from_grid(game.grid());
+    assert_eq!(board.legal_moves(Player::Black), 0);
+    let solution = Solver::new().solve(&board, Player::Black);
+    assert_eq!((solution.score, solution.best_move), (0, None));
+    assert_eq!(Engine::new(Heuristic::default(), Limit::Depth(3)).choose_move(&game), None);
+}
+
+// Test that squares holding discs cannot be blocked
+#[test]
+fn test_blocked_square_errors() {
+    let rules = Rules { blocked: vec![(3, 3)], ..Rules::standard() };
+    let error = rules.start::<8>(&mut rng()).unwrap_err();
+    assert_eq!(error, RulesError::Occupied { row: 3, col: 3 });
+    assert_eq!(error.to_string(), "dd cannot be blocked, it holds a disc at the start");
+
+    let rules = Rules { blocked: vec![(6, 6)], ..Rules::standard() };
+    assert_eq!(rules.start::<6>(&mut rng()), Err(RulesError::BadSquare(MoveError::OffBoard { row: 6, col: 6 })));
+
+    let rules = Rules { setup: Setup::Position("B W".to_string()), ..Rules::standard() };
+    assert!(matches!(rules.start::<8>(&mut rng()), Err(RulesError::BadPosition(PositionError::TooShort { .. }))));
+}
+
+// Test that random starts depend only on the seed and respect blocked squares
+#[test]
+fn test_random_start() {
+    let rules = Rules { setup: Setup::Random { plies: 6 }, blocked: vec![(2, 3), (0, 0)], ..Rules::standard() };
+    let game: Game = rules.start(&mut StdRng::seed_from_u64(1)).unwrap();
+    assert_eq!(rules.start::<8>(&mut StdRng::seed_from_u64(1)).unwrap(), game);
+    let (black, white) = game.score();
+    assert_eq!(black + white, 4 + 6);
+    assert_eq!(game.cell(2, 3), Cell::Blocked);
+    assert_eq!(game.cell(0, 0), Cell::Blocked);
+
+    let starts: Vec<Game> = (0..20).map(|seed| rules.start(&mut StdRng::seed_from_u64(seed)).unwrap()).collect();
+    assert!(starts.iter().any(|start| *start != starts[0]));
+}
+
+// Test a whole anti-reversi game: the side with fewer discs wins
+#[test]
+fn test_anti_game() {
+    let rules = Rules { goal: Goal::FewestDiscs, ..Rules::standard() };
+    assert!(!rules.has_standard_play());
+    let mut game: Game<4> = rules.start(&mut rng()).unwrap();
+    while !game.is_over() {
+        if game.must_pass() {
+            game.pass();
+            continue;
+        }
+        let (row, col) = game.legal_moves()[0];
+        game.play(row, col);
+    }
+    let (black, white) = game.score();
+    assert_ne!(black, white);
+    let fewer = if black < white { Player::Black } else { Player::White };
+    assert_eq!(game.winner(), Some(fewer));
+}
@@ -0,0 +1,163 @@
+// Import self-play dataset generation and the pattern evaluation tuner
+use reversi::engine::Limit;
+use reversi::pattern::{self, PatternEval, TuneSettings};
+use reversi::selfplay::{self, Sample, Settings};
+use reversi::{Bitboard, Engine, Game, Player};
+
+
+// A small, quick dataset
+fn settings(seed: u64) -> Settings {
+    Settings { games: 10, depth: 1, randomness: 0.1, opening_plies: 2, seed }
+}
+
+// Test that the same seed gives the same games and another seed different ones
+#[test]
+fn test_generate_is_deterministic() {
+    let first = selfplay::generate(settings(7), |_| {});
+    let second = selfplay::generate(settings(7), |_| {});
+    assert_eq!(first, second);
+    assert_ne!(first, selfplay::generate(settings(8), |_| {}));
+}
+
+// Test that progress is reported once per game
+#[test]
+fn test_generate_reports_progress() {
+    let mut reported = Vec::new();
+    selfplay::generate(settings(1), |played| reported.push(played));
+    assert_eq!(reported, (1..=10).collect::<Vec<usize>>());
+}
+
+// Test that every recorded position has a legal move and that the positions
+// of a game share its final result
+#[test]
+fn test_samples_are_playable_positions() {
+    let samples = selfplay::generate(settings(3), |_| {});
+    assert!(samples.len() > 10 * 40, "only {} positions", samples.len());
+    for sample in &samples {
+        assert_ne!(sample.board.legal_moves(sample.to_move), 0);
+        assert_eq!(sample.board.black & sample.board.white, 0);
+        assert!(sample.result.abs() <= 64);
+    }
+    // The first position of a game has the fewest discs; results only change
+    // between games
+    let mut changes = 0;
+    for pair in samples.windows(2) {
+        let discs = |sample: &Sample| (sample.board.black | sample.board.white).count_ones();
+        if discs(&pair[1]) <= discs(&pair[0]) {
+            changes += 1;
+        } else {
+            assert_eq!(pair[0].result, pair[1].result);
+        }
+    }
+    assert_eq!(changes, 9);
+}
+
+// Test that the result is turned around for White
+#[test]
+fn test_mover_result() {
+    let board = Bitboard::initial();
+    assert_eq!(Sample { board, to_move: Player::Black, result: 10 }.mover_result(), 10);
+    assert_eq!(Sample { board, to_move: Player::White, result: 10 }.mover_result(), -10);
+}
+
+// Test that a dataset survives being written and read back
+#[test]
+fn test_csv_round_trip() {
+    let samples = selfplay::generate(settings(5), |_| {});
+    let text = selfplay::to_csv(&samples);
+    assert!(text.starts_with(&format!("{}\n", selfplay::CSV_HEADER)));
+    assert_eq!(selfplay::parse_csv(&text).unwrap(), samples);
+
+    // The header is optional
+    let body = text.split_once('\n').unwrap().1;
+    assert_eq!(selfplay::parse_csv(body).unwrap(), samples);
+}
+
+// Test that bad dataset lines are reported with their line numbers
+#[test]
+fn test_csv_errors() {
+    let header = selfplay::CSV_HEADER;
+    let cases = [
+        (format!("{}\n0000000810000000,0000001008000000,B", header), 2),
+        (format!("{}\nxyz,0000001008000000,B,0", header), 2),
+        ("0000000810000000,0000001008000000,B,0\n1,1,W,0".to_string(), 2),
+        ("0000000810000000,0000001008000000,X,0".to_string(), 1),
+        ("0000000810000000,0000001008000000,B,many".to_string(), 1),
+    ];
+    for (text, line) in cases {
+        let error = selfplay::parse_csv(&text).unwrap_err();
+        assert_eq!(error.line, line, "{:?}", text);
+    }
+}
+
+// Test that tuning lowers the error on the data it is fitted to
+#[test]
+fn test_tune_reduces_error() {
+    let samples = selfplay::generate(settings(11), |_| {});
+    let start = PatternEval::new();
+    let before = pattern::mean_squared_error(&start, &samples);
+
+    let mut errors = Vec::new();
+    let settings = TuneSettings { epochs: 5, ..TuneSettings::default() };
+    let tuned = pattern::tune(start, &samples, settings, |epoch, error| errors.push((epoch, error)));
+
+    assert_eq!(errors.iter().map(|&(epoch, _)| epoch).collect::<Vec<usize>>(), vec![1, 2, 3, 4, 5]);
+    let after = pattern::mean_squared_error(&tuned, &samples);
+    assert_eq!(errors.last().unwrap().1, after);
+    assert!(after < before, "error went from {} to {}", before, after);
+}
+
+// Test that the evaluation is the same for symmetric copies of a position
+#[test]
+fn test_pattern_eval_is_symmetric() {
+    let samples = selfplay::generate(settings(13), |_| {});
+    let tuned = pattern::tune(PatternEval::new(), &samples, TuneSettings { epochs: 2, ..TuneSettings::default() }, |_, _| {});
+    for sample in samples.iter().step_by(7) {
+        let value = tuned.predict(&sample.board, sample.to_move);
+        for symmetry in 0..8 {
+            let image = sample.board.transform(symmetry);
+            assert!((tuned.predict(&image, sample.to_move) - value).abs() < 1e-3);
+        }
+    }
+}
+
+// Test that weights survive being written and read back
+#[test]
+fn test_weights_round_trip() {
+    let samples = selfplay::generate(settings(17), |_| {});
+    let tuned = pattern::tune(PatternEval::new(), &samples, TuneSettings { epochs: 1, ..TuneSettings::default() }, |_, _| {});
+    let text = tuned.to_string();
+    assert_eq!(PatternEval::parse(&text).unwrap(), tuned);
+    assert_eq!(PatternEval::parse(&format!("# tuned\n\n{}", text)).unwrap(), tuned);
+}
+
+// Test that bad weight files are rejected
+#[test]
+fn test_weights_errors() {
+    let zeros = PatternEval::new().to_string();
+    let edge = zeros.lines().next().unwrap();
+
+    let error = PatternEval::parse(edge).unwrap_err();
+    assert_eq!(error.line, 0);
+    assert_eq!(error.to_string(), "missing pattern 'corner'");
+
+    let error = PatternEval::parse("diagonal 1 2 3").unwrap_err();
+    assert_eq!(error.line, 1);
+
+    let error = PatternEval::parse("edge 1 2 3").unwrap_err();
+    assert_eq!(error.to_string(), "line 1: expected 6561 weights, found 3");
+
+    let error = PatternEval::parse(&zeros.replacen(" 0", " zero", 1)).unwrap_err();
+    assert_eq!(error.to_string(), "line 1: bad weight 'zero'");
+}
+
+// Test that tuned weights can drive the engine
+#[test]
+fn test_pattern_eval_in_engine() {
+    let samples = selfplay::generate(settings(19), |_| {});
+    let tuned = pattern::tune(PatternEval::new(), &samples, TuneSettings { epochs: 3, ..TuneSettings::default() }, |_, _| {});
+    let mut engine = Engine::new(tuned, Limit::Depth(3));
+    let game = Game::new();
+    let (row, col) = engine.choose_move(&game).unwrap();
+    assert!(game.is_legal(row, col));
+}
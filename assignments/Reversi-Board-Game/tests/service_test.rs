@@ -0,0 +1,244 @@
+// Import the HTTP game service and play games against it over localhost
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+
+use reversi::service::{serve, Service, MAX_LINE};
+use serde_json::{json, Value};
+
+
+// Start a server on a free local port
+fn start_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || serve(&listener, Arc::new(Service::new(2))));
+    addr
+}
+
+// Send one request and return the status and JSON body (null if empty)
+fn request(addr: SocketAddr, method: &str, path: &str, body: Option<Value>) -> (u16, Value) {
+    let body = body.map_or(String::new(), |body| body.to_string());
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", method, path, body.len(), body)
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    let body = if body.is_empty() { Value::Null } else { serde_json::from_str(body).unwrap() };
+    (status, body)
+}
+
+// Create a game with the given options and return its id
+fn create(addr: SocketAddr, options: Value) -> u64 {
+    let (status, game) = request(addr, "POST", "/games", Some(options));
+    assert_eq!(status, 201);
+    game["id"].as_u64().unwrap()
+}
+
+// Test that a new game starts from the standard position
+#[test]
+fn test_create_game() {
+    let addr = start_server();
+    let (status, game) = request(addr, "POST", "/games", None);
+    assert_eq!(status, 201);
+    assert_eq!(game["board"][3], "...WB...");
+    assert_eq!(game["board"][4], "...BW...");
+    assert_eq!(game["to_move"], "B");
+    assert_eq!((game["black"].clone(), game["white"].clone()), (json!(2), json!(2)));
+    assert_eq!(game["over"], false);
+    assert_eq!(game["winner"], Value::Null);
+    assert_eq!(game["variant"], "standard");
+    assert_eq!(game["history"], json!([]));
+
+    let id = game["id"].as_u64().unwrap();
+    let (status, fetched) = request(addr, "GET", &format!("/games/{}", id), None);
+    assert_eq!(status, 200);
+    assert_eq!(fetched, game);
+}
+
+// Test that games can be created with other rules, and bad rules are refused
+#[test]
+fn test_create_with_rules() {
+    let addr = start_server();
+    let (_, game) = request(addr, "POST", "/games", Some(json!({ "variant": "anti", "blocked": "aa,hh" })));
+    assert_eq!(game["variant"], "anti");
+    assert_eq!(game["board"][0], "#.......");
+    assert_eq!(game["board"][7], ".......#");
+
+    let (status, body) = request(addr, "POST", "/games", Some(json!({ "variant": "giveaway" })));
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "'giveaway' is not a variant: use standard or anti");
+    let (status, _) = request(addr, "POST", "/games", Some(json!({ "blocked": "dd" })));
+    assert_eq!(status, 400);
+    let (status, _) = request(addr, "POST", "/games", Some(json!({ "start": "random", "seed": "one" })));
+    assert_eq!(status, 400);
+
+    // The same seed gives the same random start
+    let (_, first) = request(addr, "POST", "/games", Some(json!({ "start": "random", "seed": 5 })));
+    let (_, second) = request(addr, "POST", "/games", Some(json!({ "start": "random", "seed": 5 })));
+    assert_eq!(first["board"], second["board"]);
+}
+
+// Test the legal moves listed for the opening position
+#[test]
+fn test_legal_moves() {
+    let addr = start_server();
+    let id = create(addr, json!({}));
+    let (status, body) = request(addr, "GET", &format!("/games/{}/moves", id), None);
+    assert_eq!(status, 200);
+    assert_eq!(body["to_move"], "B");
+    assert_eq!(body["must_pass"], false);
+    let moves: Vec<&str> = body["moves"].as_array().unwrap().iter().map(|mv| mv["move"].as_str().unwrap()).collect();
+    assert_eq!(moves, ["cd", "dc", "ef", "fe"]);
+    assert!(body["moves"].as_array().unwrap().iter().all(|mv| mv["flips"] == 1));
+}
+
+// Test that moves are played, and rejected moves leave the game unchanged
+#[test]
+fn test_submit_moves() {
+    let addr = start_server();
+    let id = create(addr, json!({}));
+    let path = format!("/games/{}/moves", id);
+
+    let (status, game) = request(addr, "POST", &path, Some(json!({ "move": "cd" })));
+    assert_eq!(status, 200);
+    assert_eq!(game["board"][2], "...B....");
+    assert_eq!(game["to_move"], "W");
+    assert_eq!(game["history"], json!(["cd"]));
+
+    let (status, body) = request(addr, "POST", &path, Some(json!({ "move": "aa" })));
+    assert_eq!(status, 409);
+    assert_eq!(body["error"], "aa does not flip any discs");
+    let (status, _) = request(addr, "POST", &path, Some(json!({ "move": "cd" })));
+    assert_eq!(status, 409);
+    let (status, _) = request(addr, "POST", &path, Some(json!({ "move": "pass" })));
+    assert_eq!(status, 409);
+    let (status, _) = request(addr, "POST", &path, Some(json!({ "move": "zz" })));
+    assert_eq!(status, 400);
+    let (status, _) = request(addr, "POST", &path, Some(json!({})));
+    assert_eq!(status, 400);
+    let (status, _) = request(addr, "POST", &path, None);
+    assert_eq!(status, 400);
+
+    let (_, after) = request(addr, "GET", &format!("/games/{}", id), None);
+    assert_eq!(after, game);
+}
+
+// Test that the engine moves for the side to move
+#[test]
+fn test_engine_move() {
+    let addr = start_server();
+    let id = create(addr, json!({}));
+    let path = format!("/games/{}/engine", id);
+
+    let (status, game) = request(addr, "POST", &path, Some(json!({ "depth": 1 })));
+    assert_eq!(status, 200);
+    assert_eq!(game["to_move"], "W");
+    assert_eq!(game["history"], json!([game["move"].clone()]));
+    let (status, game) = request(addr, "POST", &path, None);
+    assert_eq!(status, 200);
+    assert_eq!(game["history"].as_array().unwrap().len(), 2);
+
+    let (status, _) = request(addr, "POST", &path, Some(json!({ "depth": 0 })));
+    assert_eq!(status, 400);
+    let (status, _) = request(addr, "POST", &path, Some(json!({ "depth": 99 })));
+    assert_eq!(status, 400);
+
+    // The engine only knows the standard rules
+    let anti = create(addr, json!({ "variant": "anti" }));
+    let (status, _) = request(addr, "POST", &format!("/games/{}/engine", anti), None);
+    assert_eq!(status, 409);
+}
+
+// Test listing and deleting games, and requests the service cannot answer
+#[test]
+fn test_games_and_errors() {
+    let addr = start_server();
+    let first = create(addr, json!({}));
+    let second = create(addr, json!({}));
+    let (status, body) = request(addr, "GET", "/games", None);
+    assert_eq!(status, 200);
+    assert_eq!(body["games"], json!([first, second]));
+
+    let (status, body) = request(addr, "DELETE", &format!("/games/{}", first), None);
+    assert_eq!((status, body), (204, Value::Null));
+    let (status, body) = request(addr, "GET", &format!("/games/{}", first), None);
+    assert_eq!(status, 404);
+    assert_eq!(body["error"], format!("no game {}", first));
+    let (_, body) = request(addr, "GET", "/games", None);
+    assert_eq!(body["games"], json!([second]));
+
+    assert_eq!(request(addr, "GET", "/games/abc", None).0, 404);
+    assert_eq!(request(addr, "GET", "/players", None).0, 404);
+    assert_eq!(request(addr, "GET", &format!("/games/{}/undo", second), None).0, 404);
+    assert_eq!(request(addr, "PUT", "/games", None).0, 405);
+    assert_eq!(request(addr, "GET", &format!("/games/{}/engine", second), None).0, 405);
+    assert_eq!(request(addr, "OPTIONS", "/games", None).0, 204);
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "POST /games HTTP/1.1\r\nContent-Length: 4\r\n\r\n{{bad").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+    assert!(response.contains("Access-Control-Allow-Origin: *\r\n"));
+}
+
+// Test that request and header lines longer than the limit are refused
+#[test]
+fn test_long_lines() {
+    let addr = start_server();
+    // Exactly the limit with no line end, so the server reads everything sent
+    let long_path = format!("GET /{}", "a".repeat(MAX_LINE - 5));
+    let long_header = format!("GET /games HTTP/1.1\r\nX-Long: {}", "a".repeat(MAX_LINE - 8));
+    for (head, error) in [(long_path, "the request line is too long"), (long_header, "a header line is too long")] {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(head.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 431 Request Header Fields Too Large\r\n"), "{}", response);
+        assert!(response.ends_with(&json!({ "error": error }).to_string()), "{}", response);
+    }
+    assert_eq!(request(addr, "GET", "/games", None).0, 200);
+}
+
+// Test several games played to the end at the same time
+#[test]
+fn test_concurrent_games() {
+    let addr = start_server();
+    let players: Vec<_> = (0..4)
+        .map(|_| {
+            thread::spawn(move || {
+                let id = create(addr, json!({}));
+                let path = format!("/games/{}/engine", id);
+                loop {
+                    let (status, game) = request(addr, "POST", &path, Some(json!({ "depth": 1 })));
+                    assert_eq!(status, 200);
+                    if game["over"] == true {
+                        return (id, game);
+                    }
+                }
+            })
+        })
+        .collect();
+
+    let mut ids = Vec::new();
+    for player in players {
+        let (id, game) = player.join().unwrap();
+        let (black, white) = (game["black"].as_u64().unwrap(), game["white"].as_u64().unwrap());
+        let expected = match black.cmp(&white) {
+            std::cmp::Ordering::Greater => json!("B"),
+            std::cmp::Ordering::Less => json!("W"),
+            std::cmp::Ordering::Equal => Value::Null,
+        };
+        assert_eq!(game["winner"], expected);
+        ids.push(id);
+    }
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), 4);
+    let (_, body) = request(addr, "GET", "/games", None);
+    assert_eq!(body["games"].as_array().unwrap().len(), 4);
+}
@@ -0,0 +1,86 @@
+// Import the size-generic board functions from the lib.rs module
+use reversi::history::{parse_square_name_sized, square_name};
+use reversi::{
+    apply_move, count_pieces, create_initial_board_sized, has_valid_moves, is_supported_size,
+    is_valid_move, parse_input_sized, Game, History, Player,
+};
+
+
+// Test which board sizes are accepted
+#[test]
+fn test_supported_sizes() {
+    assert!(is_supported_size(4));
+    assert!(is_supported_size(8));
+    assert!(is_supported_size(26));
+    assert!(!is_supported_size(2));  // Too small for the starting discs
+    assert!(!is_supported_size(7));  // Odd sizes have no centre
+    assert!(!is_supported_size(28)); // Runs out of letters
+}
+
+// Test that the four starting discs are centred on every size
+#[test]
+fn test_initial_boards_centred() {
+    let board = create_initial_board_sized::<6>();
+    assert_eq!(board[2][2], 'W');
+    assert_eq!(board[2][3], 'B');
+    assert_eq!(board[3][2], 'B');
+    assert_eq!(board[3][3], 'W');
+    assert_eq!(count_pieces(&board), (2, 2));
+
+    let board = create_initial_board_sized::<10>();
+    assert_eq!(board[4][4], 'W');
+    assert_eq!(board[5][5], 'W');
+    assert_eq!(board[4][5], 'B');
+    assert_eq!(board[5][4], 'B');
+}
+
+// Test input parsing against the board size
+#[test]
+fn test_parse_input_sized() {
+    assert_eq!(parse_input_sized("ff", 6), Some((5, 5)));
+    assert_eq!(parse_input_sized("fg", 6), None);    // Column g is off a 6x6 board
+    assert_eq!(parse_input_sized("jj", 10), Some((9, 9)));
+    assert_eq!(parse_input_sized("zz", 26), Some((25, 25)));
+}
+
+// Test the move functions on a 6x6 board
+#[test]
+fn test_moves_on_small_board() {
+    let mut board = create_initial_board_sized::<6>();
+    assert!(is_valid_move(&board, 'B', 1, 2));
+    assert!(!is_valid_move(&board, 'B', 0, 0));
+    apply_move(&mut board, 'B', 1, 2);
+    assert_eq!(board[2][2], 'B');
+    assert_eq!(count_pieces(&board), (4, 1));
+    assert!(has_valid_moves(&board, 'W'));
+}
+
+// Test that a game on a 4x4 board plays out to the end
+#[test]
+fn test_game_on_tiny_board() {
+    let mut game = Game::<4>::new_sized();
+    let mut turns = 0;
+    while !game.is_over() {
+        match game.legal_moves().first() {
+            Some(&(row, col)) => assert!(game.play(row, col)),
+            None => assert!(game.pass()),
+        }
+        turns += 1;
+        assert!(turns <= 2 * 16);
+    }
+    let (black, white) = game.score();
+    assert!(black + white <= 16);
+}
+
+// Test transcripts with two-digit row numbers
+#[test]
+fn test_transcript_on_large_board() {
+    assert_eq!(square_name(9, 0), "a10");
+    assert_eq!(parse_square_name_sized("a10", 10), Some((9, 0)));
+    assert_eq!(parse_square_name_sized("a11", 10), None);
+
+    // Black's first move "e4" on a 10x10 board flips the disc on e5
+    let history = History::from_transcript_at(Game::<10>::new_sized(), "e4f4").unwrap();
+    assert_eq!(history.to_transcript(), "e4f4");
+    assert_eq!(history.game().to_move(), Player::Black);
+}
@@ -0,0 +1,100 @@
+// Import the endgame solver and the bitboard it works on
+use reversi::bitboard::{self, squares, Bitboard};
+use reversi::solver::solve;
+use reversi::{Player, Solver, SIZE};
+
+
+// Exhaustive minimax over the final disc difference, used as a reference
+fn brute_force(board: &Bitboard, player: Player) -> i32 {
+    let moves = board.legal_moves(player);
+    if moves == 0 {
+        if board.legal_moves(player.opponent()) == 0 {
+            let (black, white) = board.count();
+            let diff = black as i32 - white as i32;
+            return if player == Player::Black { diff } else { -diff };
+        }
+        return -brute_force(board, player.opponent());
+    }
+    squares(moves)
+        .map(|sq| {
+            let mut child = *board;
+            child.apply(player, sq);
+            -brute_force(&child, player.opponent())
+        })
+        .max()
+        .unwrap()
+}
+
+// Play a deterministic pseudo-random game until `empties` squares are left
+fn random_endgame(seed: u64, empties: u32) -> (Bitboard, Player) {
+    let mut state = seed;
+    let mut board = Bitboard::initial();
+    let mut player = Player::Black;
+    while board.empty().count_ones() > empties {
+        let mut moves = board.legal_moves(player);
+        if moves == 0 {
+            player = player.opponent();
+            moves = board.legal_moves(player);
+            if moves == 0 {
+                break;
+            }
+        }
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let pick = (state % moves.count_ones() as u64) as usize;
+        board.apply(player, squares(moves).nth(pick).unwrap());
+        player = player.opponent();
+    }
+    (board, player)
+}
+
+// Test that the solver agrees with exhaustive search on small endgames
+#[test]
+fn test_matches_brute_force() {
+    let mut solver = Solver::new();
+    for seed in 1..=20 {
+        let (board, player) = random_endgame(seed * 0x9e37_79b9, 9);
+        let solution = solver.solve(&board, player);
+        assert_eq!(solution.score, brute_force(&board, player), "seed {}", seed);
+
+        // The reported best move must achieve the score
+        if let Some(sq) = solution.best_move {
+            let mut child = board;
+            child.apply(player, sq);
+            assert_eq!(-brute_force(&child, player.opponent()), solution.score);
+        }
+    }
+}
+
+// Test that the transposition table does not change results
+#[test]
+fn test_table_reuse() {
+    let (board, player) = random_endgame(42, 14);
+    let mut solver = Solver::new();
+    let first = solver.solve(&board, player);
+    assert!(solver.table_len() > 0);
+    let again = solver.solve(&board, player);
+    assert_eq!(again.score, first.score);
+    assert!(again.nodes < first.nodes);
+    assert_eq!(solve(&board, player).score, first.score);
+}
+
+// Test a finished game and a forced pass
+#[test]
+fn test_game_over_and_pass() {
+    let board = Bitboard::from_board(&[['B'; SIZE]; SIZE]);
+    let solution = solve(&board, Player::White);
+    assert_eq!(solution.score, -64);
+    assert_eq!(solution.best_move, None);
+
+    // Black must pass, then White captures on c1 to win 3-0
+    let mut cells = [['.'; SIZE]; SIZE];
+    cells[0][0] = 'W';
+    cells[0][1] = 'B';
+    let board = Bitboard::from_board(&cells);
+    let solution = solve(&board, Player::Black);
+    assert_eq!(solution.best_move, None);
+    assert_eq!(solution.score, -3);
+    assert_eq!(solve(&board, Player::White).best_move, Some(bitboard::square(0, 2)));
+}
@@ -0,0 +1,97 @@
+// Import the agents and tournament runner from the lib.rs module
+use reversi::agent::{parse_agent, Agent, GreedyAgent, RandomAgent};
+use reversi::pattern::PatternEval;
+use reversi::tournament::{elo_difference, expected_score, play_game, round_robin, Record, Settings};
+use reversi::{Game, Player, SIZE};
+
+
+// Test the Elo conversions
+#[test]
+fn test_elo_math() {
+    assert_eq!(elo_difference(0.5), 0.0);
+    assert!((elo_difference(0.75) - 190.85).abs() < 0.01);
+    assert!((expected_score(elo_difference(0.64)) - 0.64).abs() < 1e-9);
+    assert!(elo_difference(1.0).is_finite());
+
+    let record = Record { wins: 3, draws: 2, losses: 5 };
+    assert_eq!(record.games(), 10);
+    assert_eq!(record.score(), 0.4);
+    assert_eq!(record.reversed(), Record { wins: 5, draws: 2, losses: 3 });
+}
+
+// Test that the greedy agent picks the move flipping the most discs
+#[test]
+fn test_greedy_agent() {
+    let mut board = [['.'; SIZE]; SIZE];
+    board[3][0] = 'B';
+    board[3][1] = 'W';
+    board[3][2] = 'W';
+    board[5][0] = 'B';
+    board[5][1] = 'W';
+    let game = Game::from_board(&board, Player::Black);
+    assert_eq!(GreedyAgent.choose_move(&game), Some((3, 3)));
+}
+
+// Test agent descriptions
+#[test]
+fn test_parse_agent() {
+    assert_eq!(parse_agent("random", 1).unwrap().name(), "random");
+    assert_eq!(parse_agent("greedy", 1).unwrap().name(), "greedy");
+    assert_eq!(parse_agent("engine:3", 1).unwrap().name(), "engine:3");
+    assert!(parse_agent("engine", 1).is_err());
+    assert!(parse_agent("engine:0", 1).is_err());
+    assert!(parse_agent("human", 1).is_err());
+}
+
+// Test that pattern agents read their weights from a file
+#[test]
+fn test_parse_pattern_agent() {
+    let path = std::env::temp_dir().join(format!("reversi-weights-{}.txt", std::process::id()));
+    std::fs::write(&path, PatternEval::new().to_string()).unwrap();
+    let spec = format!("pattern:2:{}", path.display());
+    let mut agent = parse_agent(&spec, 1).unwrap();
+    assert_eq!(agent.name(), "pattern:2");
+    assert!(agent.choose_move(&Game::new()).is_some());
+
+    std::fs::write(&path, "edge 1 2 3\n").unwrap();
+    let err = parse_agent(&spec, 1).err().unwrap();
+    assert!(err.starts_with("invalid weights in "), "{}", err);
+    std::fs::remove_file(&path).unwrap();
+    assert!(parse_agent(&spec, 1).err().unwrap().starts_with("failed to read "));
+    assert!(parse_agent("pattern:2", 1).is_err());
+    assert!(parse_agent("pattern:0:weights.txt", 1).is_err());
+}
+
+// Test that games between agents run to the end
+#[test]
+fn test_play_game() {
+    let mut black = RandomAgent::new(7);
+    let mut white = GreedyAgent;
+    let (b, w) = play_game(&mut black, &mut white, Game::new());
+    assert!(b + w <= SIZE * SIZE);
+    assert!(b + w > 4);
+}
+
+// Test that a round robin plays every pairing with both colours
+#[test]
+fn test_round_robin() {
+    let mut agents: Vec<Box<dyn Agent>> = vec![
+        parse_agent("random", 1).unwrap(),
+        parse_agent("greedy", 2).unwrap(),
+        parse_agent("engine:2", 3).unwrap(),
+    ];
+    let settings = Settings { games: 4, opening_plies: 4, seed: 9 };
+    let mut colours = [0usize; 3];
+    let standings = round_robin(&mut agents, settings, |black, _, _| colours[black] += 1);
+
+    assert_eq!(standings.names, vec!["random", "greedy", "engine:2"]);
+    assert_eq!(colours, [4, 4, 4]);
+    for i in 0..3 {
+        assert_eq!(standings.total(i).games(), 8);
+        for j in 0..3 {
+            assert_eq!(standings.records[i][j], standings.records[j][i].reversed());
+        }
+    }
+    let ratings = standings.elo_ratings();
+    assert!(ratings.iter().sum::<f64>().abs() < 1e-6);
+}
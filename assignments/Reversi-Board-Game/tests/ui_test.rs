@@ -0,0 +1,59 @@
+// Import the cursor and animation helpers of the full-screen interface
+use reversi::ui::{flipped_squares, last_play, Cursor, Direction};
+use reversi::{Game, Move};
+
+
+// Test that the cursor moves one square at a time and stops at the edges
+#[test]
+fn test_cursor_steps() {
+    let mut cursor = Cursor::<8>::new(0, 0);
+    cursor.step(Direction::Up);
+    cursor.step(Direction::Left);
+    assert_eq!(cursor, Cursor::new(0, 0));
+    cursor.step(Direction::Down);
+    cursor.step(Direction::Right);
+    cursor.step(Direction::Right);
+    assert_eq!((cursor.row, cursor.col), (1, 2));
+
+    let mut cursor = Cursor::<4>::new(9, 9);
+    assert_eq!((cursor.row, cursor.col), (3, 3));
+    cursor.step(Direction::Down);
+    cursor.step(Direction::Right);
+    assert_eq!((cursor.row, cursor.col), (3, 3));
+}
+
+// Test jumping between legal moves in both directions with wrap-around
+#[test]
+fn test_cursor_jumps_to_legal_moves() {
+    let game = Game::new();
+    let mut cursor = Cursor::new(0, 0);
+    let mut visited = Vec::new();
+    for _ in 0..5 {
+        cursor.next_legal(&game);
+        visited.push((cursor.row, cursor.col));
+    }
+    assert_eq!(visited, vec![(2, 3), (3, 2), (4, 5), (5, 4), (2, 3)]);
+
+    cursor.prev_legal(&game);
+    assert_eq!((cursor.row, cursor.col), (5, 4));
+    cursor.prev_legal(&game);
+    assert_eq!((cursor.row, cursor.col), (4, 5));
+}
+
+// Test that only discs which changed colour are animated
+#[test]
+fn test_flipped_squares() {
+    let before = Game::new();
+    let mut after = before.clone();
+    assert!(after.play(2, 3));
+    assert_eq!(flipped_squares(&before, &after), vec![(3, 3)]);
+    assert!(flipped_squares(&before, &before).is_empty());
+}
+
+// Test that the last move marker skips passes
+#[test]
+fn test_last_play() {
+    assert_eq!(last_play(&[]), None);
+    assert_eq!(last_play(&[Move::Play(2, 3), Move::Pass]), Some((2, 3)));
+    assert_eq!(last_play(&[Move::Play(2, 3), Move::Play(2, 2)]), Some((2, 2)));
+}
@@ -0,0 +1,159 @@
+// Import Zobrist hashing and the position types it keys
+use proptest::prelude::*;
+use reversi::bitboard::{self, squares, SYMMETRIES};
+use reversi::zobrist::{self, hash_board, hash_grid, transform_coords};
+use reversi::{apply_move_hashed, Bitboard, Game, History, Player};
+
+
+// Play a random game, choosing each move by index into the legal moves
+fn random_game<const N: usize>(choices: &[usize]) -> Vec<Game<N>> {
+    let mut game = Game::<N>::new_sized();
+    let mut positions = vec![game.clone()];
+    for &choice in choices {
+        if game.is_over() {
+            break;
+        }
+        if !game.pass() {
+            let moves = game.legal_moves();
+            let (row, col) = moves[choice % moves.len()];
+            game.play(row, col);
+        }
+        positions.push(game.clone());
+    }
+    positions
+}
+
+// Test that the hash depends on the side to move and on every disc
+#[test]
+fn test_hash_distinguishes_positions() {
+    let game = Game::new();
+    let flipped_side = Game::from_grid(*game.grid(), Player::White);
+    assert_eq!(game.key(), hash_grid(game.grid(), Player::Black));
+    assert_ne!(game.key(), flipped_side.key());
+    assert_eq!(game.key() ^ flipped_side.key(), zobrist::SIDE_KEY);
+
+    let mut after = game.clone();
+    after.play(2, 3);
+    assert_ne!(after.key(), game.key());
+    assert_eq!(after.key(), Game::from_board(&after.board(), Player::White).key());
+}
+
+// Test that symmetric copies of a position share a canonical key while the
+// plain keys differ
+#[test]
+fn test_canonical_keys() {
+    let openings: Vec<Game> = ["f5", "d3", "c4", "e6"]
+        .iter()
+        .map(|transcript| History::from_transcript(transcript).unwrap().game().clone())
+        .collect();
+    for game in &openings[1..] {
+        assert_ne!(game.key(), openings[0].key());
+        assert_eq!(game.canonical_key(), openings[0].canonical_key());
+    }
+
+    let other = History::from_transcript("f5d6").unwrap().game().clone();
+    assert_ne!(other.canonical_key(), openings[0].canonical_key());
+    let bitboard = Bitboard::from_grid(other.grid());
+    assert_eq!(bitboard.canonical_key(Player::Black), other.canonical_key());
+}
+
+// Test that blocked squares are part of the hash, so variants with the same
+// discs do not share keys
+#[test]
+fn test_blocked_squares_hashed() {
+    let game = Game::new();
+    let mut board = game.board();
+    board[0][0] = '#';
+    let blocked = Game::from_board(&board, Player::Black);
+    assert_ne!(blocked.key(), game.key());
+    assert_eq!(blocked.key(), game.key() ^ zobrist::blocked_key(0));
+    assert_eq!(blocked.key(), hash_board(&board, Player::Black));
+    assert_ne!(blocked.canonical_key(), game.canonical_key());
+
+    // The bitboard hashes agree, and a blocked corner is still symmetric
+    let bitboard = Bitboard::from_grid(blocked.grid());
+    assert_eq!(bitboard.key(Player::Black), blocked.key());
+    assert_eq!(bitboard.canonical_key(Player::Black), blocked.canonical_key());
+    board[0][0] = '.';
+    board[7][7] = '#';
+    assert_eq!(Game::from_board(&board, Player::Black).canonical_key(), blocked.canonical_key());
+}
+
+// Test that grid symmetries agree with the bitboard ones on 8x8
+#[test]
+fn test_transform_coords() {
+    for symmetry in 0..SYMMETRIES {
+        for sq in 0..64 {
+            let (row, col) = bitboard::coords(sq);
+            let (tr, tc) = transform_coords::<8>(row, col, symmetry);
+            assert_eq!(bitboard::square(tr, tc), bitboard::transform_square(sq, symmetry));
+        }
+    }
+}
+
+proptest! {
+    // The incremental hash of a game always equals a hash from scratch
+    #[test]
+    fn prop_incremental_hash(choices in prop::collection::vec(any::<usize>(), 60)) {
+        for game in random_game::<8>(&choices) {
+            prop_assert_eq!(game.key(), hash_grid(game.grid(), game.to_move()));
+            prop_assert_eq!(game.key(), Bitboard::from_grid(game.grid()).key(game.to_move()));
+        }
+        for game in random_game::<6>(&choices) {
+            prop_assert_eq!(game.key(), hash_grid(game.grid(), game.to_move()));
+        }
+    }
+
+    // Updating through `apply_move_hashed` and `zobrist::update` matches
+    // hashing the result
+    #[test]
+    fn prop_apply_move_hashed(choices in prop::collection::vec(any::<usize>(), 60)) {
+        let mut board = reversi::create_initial_board();
+        let mut hash = hash_board(&board, Player::Black);
+        let mut bitboard = Bitboard::initial();
+        let mut bit_hash = bitboard.key(Player::Black);
+        let mut player = Player::Black;
+        for choice in choices {
+            let mut moves = bitboard.legal_moves(player);
+            if moves == 0 {
+                player = player.opponent();
+                hash ^= zobrist::SIDE_KEY;
+                bit_hash ^= zobrist::SIDE_KEY;
+                moves = bitboard.legal_moves(player);
+                if moves == 0 {
+                    break;
+                }
+            }
+            let sq = squares(moves).nth(choice % moves.count_ones() as usize).unwrap();
+            let (row, col) = bitboard::coords(sq);
+            apply_move_hashed(&mut board, player.to_char(), row, col, &mut hash);
+            let flipped = bitboard.apply(player, sq);
+            bit_hash = zobrist::update(bit_hash, player, sq, flipped);
+            player = player.opponent();
+            prop_assert_eq!(hash, hash_board(&board, player));
+            prop_assert_eq!(bit_hash, bitboard.key(player));
+            prop_assert_eq!(hash, bit_hash);
+        }
+    }
+
+    // Every symmetric copy of a position has the same canonical key
+    #[test]
+    fn prop_canonical_key(choices in prop::collection::vec(any::<usize>(), 30), symmetry in 0..SYMMETRIES) {
+        let game = random_game::<6>(&choices).pop().unwrap();
+        let image = Game::from_grid(zobrist::transform_grid(game.grid(), symmetry), game.to_move());
+        prop_assert_eq!(image.canonical_key(), game.canonical_key());
+    }
+
+    // Games and bitboards agree on the canonical copy of an 8x8 position,
+    // which the book and database key on
+    #[test]
+    fn prop_one_canonical_copy(choices in prop::collection::vec(any::<usize>(), 30), symmetry in 0..SYMMETRIES) {
+        let game = random_game::<8>(&choices).pop().unwrap();
+        let bitboard = Bitboard::from_grid(game.grid()).transform(symmetry);
+        let (canonical, to_canonical) = bitboard.canonical();
+        prop_assert_eq!(bitboard.transform(to_canonical), canonical);
+        prop_assert_eq!(canonical.key(game.to_move()), game.canonical_key());
+        prop_assert_eq!(bitboard.canonical_key(game.to_move()), game.canonical_key());
+        prop_assert_eq!(Bitboard::from_grid(game.grid()).canonical().0, canonical);
+    }
+}